@@ -0,0 +1,117 @@
+//! An async client for komorebi, available with the `tokio` feature
+//!
+//! Unix domain sockets on Windows are not supported by tokio, so every call is run on tokio's
+//! blocking thread pool; notifications are forwarded from a dedicated blocking task
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+
+use crate::ConnectOptions;
+use crate::Monitor;
+use crate::Notification;
+use crate::Result;
+use crate::SocketMessage;
+use crate::State;
+use crate::StateQuery;
+use crate::Workspace;
+
+/// The number of notifications buffered before the reading task waits for the consumer
+const NOTIFICATION_BUFFER: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncClient {
+    options: ConnectOptions,
+}
+
+impl AsyncClient {
+    #[must_use]
+    pub const fn new(options: ConnectOptions) -> Self {
+        Self { options }
+    }
+
+    pub async fn send_message(&self, message: SocketMessage) -> Result<()> {
+        let options = self.options;
+        tokio::task::spawn_blocking(move || crate::send_message_with_options(&message, &options))
+            .await?
+    }
+
+    pub async fn send_query(&self, message: SocketMessage) -> Result<String> {
+        let options = self.options;
+        tokio::task::spawn_blocking(move || crate::send_query_with_options(&message, &options))
+            .await?
+    }
+
+    pub async fn state(&self) -> Result<State> {
+        Ok(serde_json::from_str(
+            &self.send_query(SocketMessage::State).await?,
+        )?)
+    }
+
+    pub async fn monitor(&self, monitor_idx: usize) -> Result<Monitor> {
+        self.state()
+            .await?
+            .monitors
+            .elements()
+            .get(monitor_idx)
+            .cloned()
+            .ok_or(crate::Error::NoMonitor(monitor_idx))
+    }
+
+    pub async fn workspace(&self, monitor_idx: usize, workspace_idx: usize) -> Result<Workspace> {
+        self.monitor(monitor_idx)
+            .await?
+            .workspaces()
+            .get(workspace_idx)
+            .cloned()
+            .ok_or(crate::Error::NoWorkspace(monitor_idx, workspace_idx))
+    }
+
+    pub async fn query(&self, state_query: StateQuery) -> Result<usize> {
+        let response = self.send_query(SocketMessage::Query(state_query)).await?;
+        response
+            .trim()
+            .parse()
+            .map_err(|_| crate::Error::UnexpectedResponse(response))
+    }
+
+    /// A [`Stream`] of every [`Notification`] sent over a persistent connection
+    pub async fn subscribe(&self, name: &str) -> Result<impl Stream<Item = Result<Notification>>> {
+        let name = name.to_string();
+        let notifications =
+            tokio::task::spawn_blocking(move || crate::subscribe_stream(&name)).await??;
+
+        Ok(forward(notifications))
+    }
+
+    /// A [`Stream`] of every [`Notification`], reconstructed from JSON Patch deltas
+    pub async fn subscribe_with_deltas(
+        &self,
+        name: &str,
+    ) -> Result<impl Stream<Item = Result<Notification>>> {
+        let name = name.to_string();
+        let notifications =
+            tokio::task::spawn_blocking(move || crate::subscribe_stream_with_deltas(&name))
+                .await??;
+
+        Ok(forward(notifications))
+    }
+}
+
+fn forward<I>(notifications: I) -> ReceiverStream<Result<Notification>>
+where
+    I: Iterator<Item = Result<Notification>> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(NOTIFICATION_BUFFER);
+
+    tokio::task::spawn_blocking(move || {
+        for notification in notifications {
+            // The stream has been dropped by the consumer
+            if sender.blocking_send(notification).is_err() {
+                break;
+            }
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
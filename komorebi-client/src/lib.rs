@@ -2,11 +2,14 @@
 #![allow(clippy::missing_errors_doc)]
 
 pub use komorebi::container::Container;
+pub use komorebi::json_patch;
+pub use komorebi::json_patch::PatchOperation;
 pub use komorebi::monitor::Monitor;
 pub use komorebi::ring::Ring;
 pub use komorebi::window::Window;
 pub use komorebi::window_manager_event::WindowManagerEvent;
 pub use komorebi::workspace::Workspace;
+pub use komorebi::DeltaNotification;
 pub use komorebi::Notification;
 pub use komorebi::NotificationEvent;
 pub use komorebi::State;
@@ -20,34 +23,105 @@ pub use komorebi_core::Layout;
 pub use komorebi_core::OperationDirection;
 pub use komorebi_core::Rect;
 pub use komorebi_core::SocketMessage;
+pub use komorebi_core::StateQuery;
+pub use komorebi_core::SubscriptionMode;
+
+#[cfg(feature = "tokio")]
+pub mod async_client;
 
 use komorebi::DATA_DIR;
 
+use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Read;
 use std::io::Write;
 use std::net::Shutdown;
+use std::time::Duration;
+use std::time::Instant;
 pub use uds_windows::UnixListener;
 use uds_windows::UnixStream;
 
 const KOMOREBI: &str = "komorebi.sock";
 
-pub fn send_message(message: &SocketMessage) -> std::io::Result<()> {
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("could not connect to komorebi within {0:?}: {1}")]
+    Connect(Duration, std::io::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("komorebi returned an unexpected response: {0}")]
+    UnexpectedResponse(String),
+    #[error("there is no monitor at index {0}")]
+    NoMonitor(usize),
+    #[error("there is no workspace at index {1} on monitor {0}")]
+    NoWorkspace(usize, usize),
+    #[cfg(feature = "tokio")]
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// How long to keep trying to connect to komorebi, and how long to wait between attempts
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectOptions {
+    pub timeout: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+pub fn connect(options: &ConnectOptions) -> Result<UnixStream> {
     let socket = DATA_DIR.join(KOMOREBI);
-    let mut connected = false;
-    while !connected {
-        if let Ok(mut stream) = UnixStream::connect(&socket) {
-            connected = true;
-            stream.write_all(serde_json::to_string(message)?.as_bytes())?;
+    let started = Instant::now();
+    let mut backoff = options.initial_backoff;
+
+    loop {
+        match UnixStream::connect(&socket) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => {
+                if started.elapsed() + backoff > options.timeout {
+                    return Err(Error::Connect(options.timeout, error));
+                }
+
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(options.max_backoff);
+            }
         }
     }
+}
+
+pub fn send_message(message: &SocketMessage) -> Result<()> {
+    send_message_with_options(message, &ConnectOptions::default())
+}
+
+pub fn send_message_with_options(message: &SocketMessage, options: &ConnectOptions) -> Result<()> {
+    let mut stream = connect(options)?;
+    stream.write_all(serde_json::to_string(message)?.as_bytes())?;
 
     Ok(())
 }
-pub fn send_query(message: &SocketMessage) -> std::io::Result<String> {
-    let socket = DATA_DIR.join(KOMOREBI);
 
-    let mut stream = UnixStream::connect(socket)?;
+pub fn send_query(message: &SocketMessage) -> Result<String> {
+    send_query_with_options(message, &ConnectOptions::default())
+}
+
+pub fn send_query_with_options(
+    message: &SocketMessage,
+    options: &ConnectOptions,
+) -> Result<String> {
+    let mut stream = connect(options)?;
     stream.write_all(serde_json::to_string(message)?.as_bytes())?;
     stream.shutdown(Shutdown::Write)?;
 
@@ -58,7 +132,41 @@ pub fn send_query(message: &SocketMessage) -> std::io::Result<String> {
     Ok(response)
 }
 
-pub fn subscribe(name: &str) -> std::io::Result<UnixListener> {
+pub fn state() -> Result<State> {
+    Ok(serde_json::from_str(&send_query(&SocketMessage::State)?)?)
+}
+
+pub fn monitor(monitor_idx: usize) -> Result<Monitor> {
+    let state = state()?;
+    state
+        .monitors
+        .elements()
+        .get(monitor_idx)
+        .cloned()
+        .ok_or(Error::NoMonitor(monitor_idx))
+}
+
+pub fn workspace(monitor_idx: usize, workspace_idx: usize) -> Result<Workspace> {
+    monitor(monitor_idx)?
+        .workspaces()
+        .get(workspace_idx)
+        .cloned()
+        .ok_or(Error::NoWorkspace(monitor_idx, workspace_idx))
+}
+
+pub fn query(state_query: StateQuery) -> Result<usize> {
+    let response = send_query(&SocketMessage::Query(state_query))?;
+    response
+        .trim()
+        .parse()
+        .map_err(|_| Error::UnexpectedResponse(response))
+}
+
+pub fn subscribe(name: &str) -> Result<UnixListener> {
+    subscribe_with_mode(name, SubscriptionMode::Full)
+}
+
+pub fn subscribe_with_mode(name: &str, mode: SubscriptionMode) -> Result<UnixListener> {
     let socket = DATA_DIR.join(name);
 
     match std::fs::remove_file(&socket) {
@@ -66,14 +174,133 @@ pub fn subscribe(name: &str) -> std::io::Result<UnixListener> {
         Err(error) => match error.kind() {
             std::io::ErrorKind::NotFound => {}
             _ => {
-                return Err(error);
+                return Err(error.into());
             }
         },
     };
 
     let listener = UnixListener::bind(&socket)?;
 
-    send_message(&SocketMessage::AddSubscriberSocket(name.to_string()))?;
+    send_message(&SocketMessage::AddSubscriberSocketWithMode(
+        name.to_string(),
+        mode,
+    ))?;
 
     Ok(listener)
 }
+
+fn connect_stream(name: &str, mode: SubscriptionMode) -> Result<BufReader<UnixStream>> {
+    let mut stream = connect(&ConnectOptions::default())?;
+    stream.write_all(
+        serde_json::to_string(&SocketMessage::AddSubscriberStream(name.to_string(), mode))?
+            .as_bytes(),
+    )?;
+    stream.write_all(b"\n")?;
+    stream.shutdown(Shutdown::Write)?;
+
+    Ok(BufReader::new(stream))
+}
+
+/// Open a persistent connection to komorebi which receives a newline-delimited [`Notification`]
+/// for every processed event and command
+pub fn subscribe_stream(name: &str) -> Result<NotificationStream> {
+    Ok(NotificationStream {
+        reader: connect_stream(name, SubscriptionMode::Full)?,
+    })
+}
+
+/// Open a persistent connection to komorebi which receives JSON Patch deltas, and apply them to
+/// yield a full [`Notification`] for every processed event and command
+///
+/// If a gap in the sequence numbers is observed, a fresh snapshot is requested automatically
+pub fn subscribe_stream_with_deltas(name: &str) -> Result<DeltaNotificationStream> {
+    Ok(DeltaNotificationStream {
+        name: name.to_string(),
+        reader: connect_stream(name, SubscriptionMode::Delta)?,
+        document: None,
+        sequence: 0,
+    })
+}
+
+pub struct NotificationStream {
+    reader: BufReader<UnixStream>,
+}
+
+impl Iterator for NotificationStream {
+    type Item = Result<Notification>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Option::from(serde_json::from_str(&line).map_err(Error::from)),
+            Err(error) => Option::from(Err(error.into())),
+        }
+    }
+}
+
+pub struct DeltaNotificationStream {
+    name: String,
+    reader: BufReader<UnixStream>,
+    document: Option<serde_json::Value>,
+    sequence: u64,
+}
+
+impl DeltaNotificationStream {
+    fn resync(&mut self) -> Result<()> {
+        self.document = None;
+        send_message(&SocketMessage::ResyncSubscriber(self.name.clone()))
+    }
+}
+
+impl Iterator for DeltaNotificationStream {
+    type Item = Result<Notification>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(error) => return Option::from(Err(error.into())),
+            }
+
+            let delta: DeltaNotification = match serde_json::from_str(&line) {
+                Ok(delta) => delta,
+                Err(error) => return Option::from(Err(error.into())),
+            };
+
+            match delta {
+                DeltaNotification::Snapshot {
+                    sequence,
+                    notification,
+                } => {
+                    self.sequence = sequence;
+                    self.document = Option::from(notification);
+                }
+                DeltaNotification::Patch { sequence, patch } => {
+                    let applied = match self.document.as_mut() {
+                        Some(document) if sequence == self.sequence + 1 => {
+                            json_patch::apply(document, &patch).is_ok()
+                        }
+                        _ => false,
+                    };
+
+                    if !applied {
+                        if let Err(error) = self.resync() {
+                            return Option::from(Err(error));
+                        }
+
+                        continue;
+                    }
+
+                    self.sequence = sequence;
+                }
+            }
+
+            if let Some(document) = &self.document {
+                return Option::from(serde_json::from_value(document.clone()).map_err(Error::from));
+            }
+        }
+    }
+}
@@ -14,6 +14,8 @@ use serde::Serialize;
 use strum::Display;
 use strum::EnumString;
 
+use crate::config_generation::IdWithIdentifier;
+
 pub use arrangement::Arrangement;
 pub use arrangement::Axis;
 pub use custom_layout::CustomLayout;
@@ -58,6 +60,19 @@ pub enum SocketMessage {
     CycleSendContainerToWorkspace(CycleDirection),
     SendContainerToMonitorWorkspaceNumber(usize, usize),
     SendContainerToNamedWorkspace(String),
+    WithSelector(WindowSelector, Box<SocketMessage>),
+    RunOrRaise(IdWithIdentifier, Option<String>, Option<(usize, usize)>),
+    FocusWindowByHwnd(isize),
+    FocusPreviousWindow,
+    CycleFocusHistory(CycleDirection),
+    MarkWindow(String),
+    FocusMark(String),
+    SwapWithMark(String),
+    TagWindow(String),
+    UntagWindow(String),
+    ViewTags(Vec<String>),
+    SendToScratchpad(String),
+    ToggleScratchpad(String),
     MoveWorkspaceToMonitorNumber(usize),
     SwapWorkspacesToMonitorNumber(usize),
     ForceFocus,
@@ -66,12 +81,14 @@ pub enum SocketMessage {
     Promote,
     PromoteFocus,
     ToggleFloat,
+    ToggleSticky(StickyScope),
     ToggleMonocle,
     ToggleMaximize,
     ToggleWindowContainerBehaviour,
     WindowHidingBehaviour(HidingBehaviour),
     ToggleCrossMonitorMoveBehaviour,
     CrossMonitorMoveBehaviour(MoveBehaviour),
+    MonitorNavigationWrap(bool),
     UnmanagedWindowOperationBehaviour(OperationBehaviour),
     // Current Workspace Commands
     ManageFocusedWindow,
@@ -82,12 +99,15 @@ pub enum SocketMessage {
     CycleLayout(CycleDirection),
     ChangeLayoutCustom(PathBuf),
     FlipLayout(Axis),
+    Undo,
+    Redo,
     // Monitor and Workspace Commands
     MonitorIndexPreference(usize, i32, i32, i32, i32),
     DisplayIndexPreference(usize, String),
     EnsureWorkspaces(usize, usize),
     EnsureNamedWorkspaces(usize, Vec<String>),
     NewWorkspace,
+    DynamicWorkspaces(usize, bool),
     ToggleTiling,
     Stop,
     TogglePause,
@@ -96,14 +116,21 @@ pub enum SocketMessage {
     QuickLoad,
     Save(PathBuf),
     Load(PathBuf),
+    SaveSnapshot(String, SnapshotScope),
+    LoadSnapshot(String),
+    DeleteSnapshot(String),
+    Snapshots,
     CycleFocusMonitor(CycleDirection),
     CycleFocusWorkspace(CycleDirection),
     FocusMonitorNumber(usize),
+    ToggleVirtualMonitors,
     FocusLastWorkspace,
+    PopWorkspaceHistory,
     FocusWorkspaceNumber(usize),
     FocusWorkspaceNumbers(usize),
     FocusMonitorWorkspaceNumber(usize, usize),
     FocusNamedWorkspace(String),
+    FocusWorkspaceGroup(String),
     ContainerPadding(usize, usize, i32),
     NamedWorkspaceContainerPadding(String, i32),
     FocusedWorkspaceContainerPadding(i32),
@@ -128,6 +155,7 @@ pub enum SocketMessage {
     ReloadStaticConfiguration(PathBuf),
     WatchConfiguration(bool),
     CompleteConfiguration,
+    RunMacro(String, Vec<String>),
     AltFocusHack(bool),
     ActiveWindowBorder(bool),
     ActiveWindowBorderColour(WindowKind, u32, u32, u32),
@@ -142,6 +170,8 @@ pub enum SocketMessage {
     WorkspaceRule(ApplicationIdentifier, String, usize, usize),
     NamedWorkspaceRule(ApplicationIdentifier, String, String),
     FloatRule(ApplicationIdentifier, String),
+    StickyRule(ApplicationIdentifier, String),
+    ScratchpadRule(String, ApplicationIdentifier, String),
     UnmanageRule(ApplicationIdentifier, String),
     ManageRule(ApplicationIdentifier, String),
     IdentifyObjectNameChangeApplication(ApplicationIdentifier, String),
@@ -150,6 +180,9 @@ pub enum SocketMessage {
     IdentifyBorderOverflowApplication(ApplicationIdentifier, String),
     State,
     VisibleWindows,
+    SearchWindows(Option<String>, SearchMode),
+    FocusHistory,
+    Marks,
     Query(StateQuery),
     FocusFollowsMouse(FocusFollowsMouseImplementation, bool),
     ToggleFocusFollowsMouse(FocusFollowsMouseImplementation),
@@ -161,6 +194,14 @@ pub enum SocketMessage {
     RemoveSubscriberSocket(String),
     AddSubscriberPipe(String),
     RemoveSubscriberPipe(String),
+    AddSubscriberSocketWithMode(String, SubscriptionMode),
+    AddSubscriberPipeWithMode(String, SubscriptionMode),
+    AddSubscriberStream(String, SubscriptionMode),
+    ResyncSubscriber(String),
+    SubscriberOverflowPolicy(OverflowPolicy),
+    SubscriberQueueCapacity(usize),
+    SubscriptionMetrics,
+    Authenticate(String),
     ApplicationSpecificConfigurationSchema,
     NotificationSchema,
     SocketSchema,
@@ -172,6 +213,108 @@ impl SocketMessage {
     pub fn as_bytes(&self) -> Result<Vec<u8>> {
         Ok(serde_json::to_string(self)?.as_bytes().to_vec())
     }
+
+    /// Messages which only read from the window manager state and never change it
+    #[must_use]
+    pub const fn is_query(&self) -> bool {
+        matches!(
+            self,
+            Self::State
+                | Self::VisibleWindows
+                | Self::SearchWindows(..)
+                | Self::FocusHistory
+                | Self::Marks
+                | Self::Snapshots
+                | Self::Query(_)
+                | Self::SubscriptionMetrics
+                | Self::ApplicationSpecificConfigurationSchema
+                | Self::NotificationSchema
+                | Self::SocketSchema
+                | Self::StaticConfigSchema
+                | Self::GenerateStaticConfig
+        )
+    }
+
+    /// Messages which can be run on windows other than the focused window with
+    /// [`SocketMessage::WithSelector`]
+    #[must_use]
+    pub const fn accepts_selector(&self) -> bool {
+        matches!(
+            self,
+            Self::Close
+                | Self::Minimize
+                | Self::ToggleFloat
+                | Self::MarkWindow(_)
+                | Self::TagWindow(_)
+                | Self::UntagWindow(_)
+                | Self::Promote
+                | Self::StackWindow(_)
+                | Self::UnstackWindow
+                | Self::MoveWindow(_)
+                | Self::MoveContainerToMonitorNumber(_)
+                | Self::MoveContainerToWorkspaceNumber(_)
+                | Self::MoveContainerToNamedWorkspace(_)
+                | Self::SendContainerToMonitorNumber(_)
+                | Self::SendContainerToWorkspaceNumber(_)
+                | Self::SendContainerToMonitorWorkspaceNumber(_, _)
+                | Self::SendContainerToNamedWorkspace(_)
+        )
+    }
+
+    /// Messages which rearrange windows, and which can be reverted with [`SocketMessage::Undo`];
+    /// a macro is reverted as a whole, along with every message it ran
+    #[must_use]
+    pub fn is_journaled(&self) -> bool {
+        if let Self::WithSelector(_, message) = self {
+            return message.is_journaled();
+        }
+
+        matches!(
+            self,
+            Self::RunMacro(..)
+                | Self::MoveWindow(_)
+                | Self::CycleMoveWindow(_)
+                | Self::StackWindow(_)
+                | Self::UnstackWindow
+                | Self::ResizeWindowEdge(_, _)
+                | Self::ResizeWindowAxis(_, _)
+                | Self::MoveContainerToMonitorNumber(_)
+                | Self::CycleMoveContainerToMonitor(_)
+                | Self::MoveContainerToWorkspaceNumber(_)
+                | Self::MoveContainerToNamedWorkspace(_)
+                | Self::CycleMoveContainerToWorkspace(_)
+                | Self::SendContainerToMonitorNumber(_)
+                | Self::CycleSendContainerToMonitor(_)
+                | Self::SendContainerToWorkspaceNumber(_)
+                | Self::CycleSendContainerToWorkspace(_)
+                | Self::SendContainerToMonitorWorkspaceNumber(_, _)
+                | Self::SendContainerToNamedWorkspace(_)
+                | Self::MoveWorkspaceToMonitorNumber(_)
+                | Self::SwapWorkspacesToMonitorNumber(_)
+                | Self::SwapWithMark(_)
+                | Self::LoadSnapshot(_)
+                | Self::Promote
+                | Self::PromoteFocus
+                | Self::ToggleFloat
+                | Self::ToggleMonocle
+                | Self::AdjustContainerPadding(_, _)
+                | Self::AdjustWorkspacePadding(_, _)
+                | Self::ChangeLayout(_)
+                | Self::CycleLayout(_)
+                | Self::ChangeLayoutCustom(_)
+                | Self::FlipLayout(_)
+                | Self::ContainerPadding(_, _, _)
+                | Self::NamedWorkspaceContainerPadding(_, _)
+                | Self::FocusedWorkspaceContainerPadding(_)
+                | Self::WorkspacePadding(_, _, _)
+                | Self::NamedWorkspacePadding(_, _)
+                | Self::FocusedWorkspacePadding(_)
+                | Self::WorkspaceLayout(_, _, _)
+                | Self::NamedWorkspaceLayout(_, _)
+                | Self::WorkspaceLayoutCustom(_, _, _)
+                | Self::NamedWorkspaceLayoutCustom(_, _)
+        )
+    }
 }
 
 impl FromStr for SocketMessage {
@@ -182,6 +325,34 @@ impl FromStr for SocketMessage {
     }
 }
 
+#[derive(
+    Copy, Clone, Debug, Default, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SearchMode {
+    /// Case-insensitive substring match on the title, exe and class
+    #[default]
+    Substring,
+    /// Regular expression match on the title, exe and class
+    Regex,
+    /// Case-insensitive fuzzy match on the title, exe and class, ordered by score
+    Fuzzy,
+}
+
+/// The managed windows that a [`SocketMessage::WithSelector`] command is run on
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "content")]
+pub enum WindowSelector {
+    /// The window with this handle
+    Hwnd(isize),
+    /// Every window on any workspace matching this application identifier
+    Application(IdWithIdentifier),
+    /// Every window on the workspace at this monitor index and workspace index
+    Workspace(usize, usize),
+    /// Every window on the workspace with this name
+    NamedWorkspace(String),
+}
+
 #[derive(
     Copy, Clone, Debug, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
 )]
@@ -203,6 +374,40 @@ pub enum StateQuery {
     FocusedWindowIndex,
 }
 
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    ValueEnum,
+    JsonSchema,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SubscriptionMode {
+    /// Send the full state with every notification
+    #[default]
+    Full,
+    /// Send a full snapshot once, followed by JSON Patch (RFC 6902) deltas against the previous state
+    Delta,
+}
+
+#[derive(
+    Copy, Clone, Debug, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued notification to make room for the newest one
+    DropOldest,
+    /// Remove the subscription; the process will have to subscribe again
+    Disconnect,
+}
+
 #[derive(
     Copy,
     Clone,
@@ -261,6 +466,28 @@ pub enum MoveBehaviour {
     Insert,
 }
 
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum StickyScope {
+    /// Stay visible when switching workspaces on the monitor the window is on
+    Monitor,
+    /// Stay visible when switching workspaces and follow focus onto other monitors
+    AllMonitors,
+}
+
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SnapshotScope {
+    /// The arrangement of the focused workspace
+    Workspace,
+    /// The arrangement of every workspace on every monitor
+    AllMonitors,
+}
+
 #[derive(
     Copy, Clone, Debug, Serialize, Deserialize, Display, EnumString, ValueEnum, JsonSchema,
 )]
@@ -0,0 +1,113 @@
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use serde_json::Value;
+
+use komorebi_core::SocketMessage;
+
+use crate::MACROS;
+
+/// Expand a macro from the static configuration into the messages it runs, replacing `$1`,
+/// `$2`, ... in its arguments with the given parameters
+pub fn expand(name: &str, parameters: &[String]) -> Result<Vec<SocketMessage>> {
+    let steps = MACROS
+        .lock()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| anyhow!("there is no macro named {name}"))?;
+
+    let mut messages = vec![];
+
+    for step in steps {
+        let expected = highest_placeholder(&step);
+        if parameters.len() < expected {
+            bail!(
+                "macro {name} expects {expected} parameters but {} were given",
+                parameters.len()
+            );
+        }
+
+        // Parameters that look like numbers or booleans are tried as such first, so that they
+        // can be used for indices; failing that, every parameter is substituted as a string
+        let message = serde_json::from_value(substitute(&step, parameters, true))
+            .or_else(|_| serde_json::from_value(substitute(&step, parameters, false)))
+            .map_err(|error| anyhow!("macro {name} has an invalid step: {error}"))?;
+
+        if matches!(message, SocketMessage::RunMacro(..)) {
+            bail!("macro {name} cannot run another macro");
+        }
+
+        messages.push(message);
+    }
+
+    Ok(messages)
+}
+
+fn placeholder(idx: usize) -> String {
+    format!("${}", idx + 1)
+}
+
+fn highest_placeholder(value: &Value) -> usize {
+    match value {
+        Value::String(string) => {
+            let mut highest = 0;
+            let mut rest = string.as_str();
+
+            while let Some(start) = rest.find('$') {
+                rest = &rest[start + 1..];
+                let digits = rest
+                    .chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>();
+                if let Ok(n) = digits.parse::<usize>() {
+                    highest = highest.max(n);
+                }
+            }
+
+            highest
+        }
+        Value::Array(values) => values.iter().map(highest_placeholder).max().unwrap_or(0),
+        Value::Object(map) => map.values().map(highest_placeholder).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn substitute(value: &Value, parameters: &[String], typed: bool) -> Value {
+    match value {
+        Value::String(string) => {
+            for (idx, parameter) in parameters.iter().enumerate() {
+                if *string == placeholder(idx) {
+                    if typed {
+                        if let Ok(parsed @ (Value::Number(_) | Value::Bool(_))) =
+                            serde_json::from_str::<Value>(parameter)
+                        {
+                            return parsed;
+                        }
+                    }
+
+                    return Value::String(parameter.clone());
+                }
+            }
+
+            // Substitute from the highest index down so that $1 does not clobber the start of $10
+            let mut substituted = string.clone();
+            for (idx, parameter) in parameters.iter().enumerate().rev() {
+                substituted = substituted.replace(&placeholder(idx), parameter);
+            }
+
+            Value::String(substituted)
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| substitute(value, parameters, typed))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), substitute(value, parameters, typed)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use color_eyre::Result;
+
+use komorebi_core::CycleDirection;
+use komorebi_core::SearchMode;
+
+use crate::window_manager::WindowManager;
+use crate::window_search::WindowSearchResult;
+use crate::FOCUS_HISTORY;
+use crate::WINDOW_LAST_FOCUSED;
+use crate::WORKSPACE_HISTORY;
+
+const FOCUS_HISTORY_LIMIT: usize = 128;
+const WORKSPACE_HISTORY_LIMIT: usize = 32;
+
+/// Windows ordered from most to least recently focused, and the position reached by
+/// [`SocketMessage::CycleFocusHistory`](komorebi_core::SocketMessage::CycleFocusHistory) if a
+/// cycle is in progress
+#[derive(Debug, Default)]
+pub struct FocusHistory {
+    windows: VecDeque<isize>,
+    cursor: Option<usize>,
+}
+
+/// Record that a window has just been focused
+pub fn record_focus(hwnd: isize) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| {
+            u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+        });
+
+    WINDOW_LAST_FOCUSED.lock().insert(hwnd, timestamp);
+
+    let mut history = FOCUS_HISTORY.lock();
+
+    // Focusing the window reached by cycling does not reorder the history, so that the
+    // next cycle continues from the same place
+    if let Some(cursor) = history.cursor {
+        if history.windows.get(cursor) == Some(&hwnd) {
+            return;
+        }
+    }
+
+    history.cursor = None;
+    history.windows.retain(|h| *h != hwnd);
+    history.windows.push_front(hwnd);
+    history.windows.truncate(FOCUS_HISTORY_LIMIT);
+}
+
+/// Forget a window which has been destroyed or unmanaged
+pub fn forget_focus(hwnd: isize) {
+    WINDOW_LAST_FOCUSED.lock().remove(&hwnd);
+
+    let mut history = FOCUS_HISTORY.lock();
+    history.cursor = None;
+    history.windows.retain(|h| *h != hwnd);
+}
+
+/// Record the workspace that has just been left, whether by a command or by an event such as a
+/// window on another workspace being focused
+pub fn push_workspace(monitor_idx: usize, workspace_idx: usize) {
+    let mut history = WORKSPACE_HISTORY.lock();
+    if history.last() != Some(&(monitor_idx, workspace_idx)) {
+        history.push((monitor_idx, workspace_idx));
+    }
+
+    if history.len() > WORKSPACE_HISTORY_LIMIT {
+        history.remove(0);
+    }
+}
+
+/// Forget a workspace which has been removed, moving along the workspaces after it
+pub fn forget_workspace(monitor_idx: usize, workspace_idx: usize) {
+    let mut history = WORKSPACE_HISTORY.lock();
+    history.retain(|entry| *entry != (monitor_idx, workspace_idx));
+
+    for (m, w) in history.iter_mut() {
+        if *m == monitor_idx && *w > workspace_idx {
+            *w -= 1;
+        }
+    }
+
+    history.dedup();
+}
+
+impl WindowManager {
+    /// Drop windows which are no longer managed from the focus history
+    fn prune_focus_history(&self) {
+        let mut history = FOCUS_HISTORY.lock();
+        let before = history.windows.len();
+        history
+            .windows
+            .retain(|hwnd| self.window_location(*hwnd).is_some());
+
+        if history.windows.len() != before {
+            history.cursor = None;
+        }
+    }
+
+    /// Focus the window which was focused before the current one; repeating this toggles
+    /// between the two windows
+    #[tracing::instrument(skip(self))]
+    pub fn focus_previous_window(&mut self) -> Result<()> {
+        self.prune_focus_history();
+
+        let previous = {
+            let mut history = FOCUS_HISTORY.lock();
+            history.cursor = None;
+            history.windows.get(1).copied()
+        };
+
+        match previous {
+            None => {
+                tracing::info!("there is no previously focused window");
+                Ok(())
+            }
+            Some(hwnd) => self.focus_window_by_hwnd(hwnd),
+        }
+    }
+
+    /// Walk through the focus history without reordering it until another window is focused
+    #[tracing::instrument(skip(self))]
+    pub fn cycle_focus_history(&mut self, direction: CycleDirection) -> Result<()> {
+        self.prune_focus_history();
+
+        let target = {
+            let mut history = FOCUS_HISTORY.lock();
+            let len = history.windows.len();
+            if len < 2 {
+                None
+            } else {
+                let current = history.cursor.unwrap_or(0);
+                let next = match direction {
+                    CycleDirection::Next => (current + 1) % len,
+                    CycleDirection::Previous => (current + len - 1) % len,
+                };
+
+                history.cursor = Option::from(next);
+                history.windows.get(next).copied()
+            }
+        };
+
+        match target {
+            None => {
+                tracing::info!("there are no other windows in the focus history");
+                Ok(())
+            }
+            Some(hwnd) => self.focus_window_by_hwnd(hwnd),
+        }
+    }
+
+    /// Managed windows from most to least recently focused
+    pub fn focus_history(&self) -> Result<Vec<WindowSearchResult>> {
+        self.prune_focus_history();
+
+        let history = FOCUS_HISTORY.lock().windows.clone();
+        let mut windows = self.search_windows(None, SearchMode::Substring)?;
+        windows.retain(|window| history.contains(&window.hwnd));
+        windows.sort_by_key(|window| history.iter().position(|hwnd| *hwnd == window.hwnd));
+
+        Ok(windows)
+    }
+
+    /// Return to the most recently left workspace which still exists, removing it from the
+    /// workspace history
+    #[tracing::instrument(skip(self))]
+    pub fn pop_workspace_history(&mut self) -> Result<()> {
+        loop {
+            let (entry, remaining) = {
+                let mut history = WORKSPACE_HISTORY.lock();
+                (history.pop(), history.clone())
+            };
+
+            let Some((monitor_idx, workspace_idx)) = entry else {
+                tracing::info!("the workspace history is empty");
+                return Ok(());
+            };
+
+            let exists = self
+                .monitors()
+                .get(monitor_idx)
+                .is_some_and(|monitor| monitor.workspaces().get(workspace_idx).is_some());
+
+            if exists {
+                self.focus_monitor(monitor_idx)?;
+                self.focus_workspace(workspace_idx)?;
+
+                // Going back must not record the workspaces being left, otherwise popping
+                // again would only toggle between the last two workspaces
+                let mut history = WORKSPACE_HISTORY.lock();
+                *history = remaining;
+
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+
+use komorebi_core::Axis;
+use komorebi_core::Layout;
+use komorebi_core::Rect;
+
+use crate::container::Container;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::workspace::Workspace;
+use crate::HISTORY_DEPTH;
+
+/// A stack of windows by handle, from the bottom to the top
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ContainerArrangement {
+    windows: Vec<isize>,
+    focused_window_idx: usize,
+}
+
+/// Where the windows of a workspace are and how it is laid out, by window handle
+#[derive(Debug, Clone, PartialEq)]
+struct WorkspaceArrangement {
+    /// Tiled containers from the first to the last, including the monocle container
+    containers: Vec<ContainerArrangement>,
+    focused_container_idx: usize,
+    /// Position of the monocle container among the tiled containers, if there was one
+    monocle_container_idx: Option<usize>,
+    floating_windows: Vec<isize>,
+    layout: Layout,
+    layout_flip: Option<Axis>,
+    workspace_padding: Option<i32>,
+    container_padding: Option<i32>,
+    resize_dimensions: Vec<Option<Rect>>,
+}
+
+impl From<&Workspace> for WorkspaceArrangement {
+    fn from(workspace: &Workspace) -> Self {
+        let arrange_container = |container: &Container| ContainerArrangement {
+            windows: container
+                .windows()
+                .iter()
+                .map(|window| window.hwnd)
+                .collect(),
+            focused_window_idx: container.focused_window_idx(),
+        };
+
+        let mut containers = workspace
+            .containers()
+            .iter()
+            .map(arrange_container)
+            .collect::<Vec<_>>();
+
+        let mut monocle_container_idx = None;
+        if let Some(container) = workspace.monocle_container() {
+            let idx = workspace
+                .monocle_container_restore_idx()
+                .unwrap_or_default()
+                .min(containers.len());
+
+            containers.insert(idx, arrange_container(container));
+            monocle_container_idx = Option::from(idx);
+        }
+
+        Self {
+            containers,
+            focused_container_idx: workspace.focused_container_idx(),
+            monocle_container_idx,
+            floating_windows: workspace
+                .floating_windows()
+                .iter()
+                .map(|window| window.hwnd)
+                .collect(),
+            layout: workspace.layout().clone(),
+            layout_flip: workspace.layout_flip(),
+            workspace_padding: workspace.workspace_padding(),
+            container_padding: workspace.container_padding(),
+            resize_dimensions: workspace.resize_dimensions().clone(),
+        }
+    }
+}
+
+impl WorkspaceArrangement {
+    fn hwnds(&self) -> impl Iterator<Item = isize> + '_ {
+        self.containers
+            .iter()
+            .flat_map(|container| container.windows.iter())
+            .chain(self.floating_windows.iter())
+            .copied()
+    }
+
+    /// Arrange a workspace with the windows which have been taken from wherever they were, in
+    /// their recorded places; windows which were opened since follow the rearranged ones
+    fn apply(&self, workspace: &mut Workspace, taken: &HashSet<isize>) -> Result<()> {
+        workspace.set_layout(self.layout.clone());
+        workspace.set_layout_flip(self.layout_flip);
+        workspace.set_workspace_padding(self.workspace_padding);
+        workspace.set_container_padding(self.container_padding);
+
+        let mut restored = 0;
+        let mut monocle_idx = None;
+
+        for (container_idx, arrangement) in self.containers.iter().enumerate() {
+            let mut container = Container::default();
+            for hwnd in arrangement
+                .windows
+                .iter()
+                .filter(|hwnd| taken.contains(hwnd))
+            {
+                container.add_window(Window { hwnd: *hwnd });
+            }
+
+            if container.windows().is_empty() {
+                continue;
+            }
+
+            container.focus_window(
+                arrangement
+                    .focused_window_idx
+                    .min(container.windows().len() - 1),
+            );
+
+            if container.windows().len() > 1 {
+                container.load_focused_window()?;
+            }
+
+            if self.monocle_container_idx == Option::from(container_idx) {
+                monocle_idx = Option::from(restored);
+            }
+
+            workspace.insert_container_at_idx(restored, container);
+            restored += 1;
+        }
+
+        // Resize adjustments only make sense if all of the containers are where they were
+        let container_count = workspace.containers().len();
+        if restored == self.containers.len() && self.resize_dimensions.len() == container_count {
+            workspace.set_resize_dimensions(self.resize_dimensions.clone());
+        } else {
+            workspace.set_resize_dimensions(vec![None; container_count]);
+        }
+
+        if container_count > 0 {
+            workspace.focus_container(self.focused_container_idx.min(container_count - 1));
+        }
+
+        if let Some(idx) = monocle_idx {
+            if workspace.monocle_container().is_none() {
+                workspace.focus_container(idx);
+                workspace.new_monocle_container()?;
+            }
+        }
+
+        for hwnd in self
+            .floating_windows
+            .iter()
+            .filter(|hwnd| taken.contains(hwnd))
+        {
+            workspace
+                .floating_windows_mut()
+                .push(Window { hwnd: *hwnd });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MonitorArrangement {
+    /// Identifies the display, see [`Monitor::device_key`](crate::monitor::Monitor::device_key)
+    device_key: String,
+    focused_workspace_idx: usize,
+    workspaces: Vec<WorkspaceArrangement>,
+}
+
+/// The arrangement of every workspace on every monitor, which is compared before and after an
+/// operation to find out what it changed
+#[derive(Debug, Clone)]
+struct Arrangement {
+    focused_monitor: Option<String>,
+    monitors: Vec<MonitorArrangement>,
+}
+
+impl From<&WindowManager> for Arrangement {
+    fn from(wm: &WindowManager) -> Self {
+        Self {
+            focused_monitor: wm.focused_monitor().map(|monitor| monitor.device_key()),
+            monitors: wm
+                .monitors()
+                .iter()
+                .map(|monitor| MonitorArrangement {
+                    device_key: monitor.device_key(),
+                    focused_workspace_idx: monitor.focused_workspace_idx(),
+                    workspaces: monitor
+                        .workspaces()
+                        .iter()
+                        .map(WorkspaceArrangement::from)
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Arrangement {
+    /// The operations which return from the current arrangement to this one, covering only the
+    /// workspaces and the focus which differ between them
+    fn inverse_from(&self, current: &Self) -> Vec<Inverse> {
+        let mut inverse = vec![];
+
+        for monitor in &self.monitors {
+            let Some(current_monitor) = current
+                .monitors
+                .iter()
+                .find(|current_monitor| current_monitor.device_key == monitor.device_key)
+            else {
+                continue;
+            };
+
+            for (workspace_idx, workspace) in monitor.workspaces.iter().enumerate() {
+                if current_monitor.workspaces.get(workspace_idx) != Option::from(workspace) {
+                    inverse.push(Inverse::Rearrange {
+                        device_key: monitor.device_key.clone(),
+                        workspace_idx,
+                        arrangement: workspace.clone(),
+                    });
+                }
+            }
+
+            if current_monitor.focused_workspace_idx != monitor.focused_workspace_idx {
+                inverse.push(Inverse::FocusWorkspace {
+                    device_key: monitor.device_key.clone(),
+                    workspace_idx: monitor.focused_workspace_idx,
+                });
+            }
+        }
+
+        if let Some(device_key) = &self.focused_monitor {
+            if current.focused_monitor.as_ref() != Option::from(device_key) {
+                inverse.push(Inverse::FocusMonitor {
+                    device_key: device_key.clone(),
+                });
+            }
+        }
+
+        inverse
+    }
+}
+
+/// Reverts one part of a journaled operation
+#[derive(Debug, Clone)]
+enum Inverse {
+    /// Put a workspace back into an earlier arrangement
+    Rearrange {
+        device_key: String,
+        workspace_idx: usize,
+        arrangement: WorkspaceArrangement,
+    },
+    /// Focus the workspace which was focused on a monitor
+    FocusWorkspace {
+        device_key: String,
+        workspace_idx: usize,
+    },
+    /// Focus the monitor which was focused
+    FocusMonitor { device_key: String },
+}
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    undo: VecDeque<Vec<Inverse>>,
+    redo: Vec<Vec<Inverse>>,
+    /// Set while a journaled operation runs, so that the messages it runs in turn are recorded
+    /// as part of it
+    recording: bool,
+}
+
+impl Journal {
+    pub const fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn push_undo(&mut self, entry: Vec<Inverse>) {
+        let depth = HISTORY_DEPTH.load(Ordering::SeqCst);
+
+        self.undo.push_back(entry);
+
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    fn record(&mut self, entry: Vec<Inverse>) {
+        if entry.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.push_undo(entry);
+    }
+}
+
+impl WindowManager {
+    /// Run an operation which rearranges windows as a single journal entry, including any
+    /// messages that it runs in turn
+    pub fn journaled<T>(&mut self, operation: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.journal.recording {
+            return operation(self);
+        }
+
+        let before = Arrangement::from(&*self);
+
+        self.journal.recording = true;
+        let result = operation(self);
+        self.journal.recording = false;
+
+        // Whatever changed is recorded even if the operation failed part of the way through
+        let entry = before.inverse_from(&Arrangement::from(&*self));
+        self.journal.record(entry);
+
+        result
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn undo(&mut self) -> Result<()> {
+        let Some(entry) = self.journal.undo.pop_back() else {
+            tracing::info!("there is nothing to undo");
+            return Ok(());
+        };
+
+        tracing::info!("undoing last operation");
+
+        let redo = self.revert(&entry)?;
+        if !redo.is_empty() {
+            self.journal.redo.push(redo);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn redo(&mut self) -> Result<()> {
+        let Some(entry) = self.journal.redo.pop() else {
+            tracing::info!("there is nothing to redo");
+            return Ok(());
+        };
+
+        tracing::info!("redoing last undone operation");
+
+        let undo = self.revert(&entry)?;
+        if !undo.is_empty() {
+            self.journal.push_undo(undo);
+        }
+
+        Ok(())
+    }
+
+    /// Apply the inverse operations of a journal entry, returning the operations which revert
+    /// them in turn; windows which have been closed and monitors which have been disconnected
+    /// since the entry was recorded are skipped
+    fn revert(&mut self, entry: &[Inverse]) -> Result<Vec<Inverse>> {
+        let before = Arrangement::from(&*self);
+
+        let resolve = |wm: &Self, device_key: &str| {
+            wm.monitors()
+                .iter()
+                .position(|monitor| monitor.device_key() == device_key)
+        };
+
+        let mut rearrangements = vec![];
+        for inverse in entry {
+            if let Inverse::Rearrange {
+                device_key,
+                workspace_idx,
+                arrangement,
+            } = inverse
+            {
+                match resolve(self, device_key) {
+                    Some(idx) => rearrangements.push((idx, *workspace_idx, arrangement)),
+                    None => tracing::warn!("skipping disconnected monitor {device_key}"),
+                }
+            }
+        }
+
+        // Every window which is still managed is taken from wherever it is now before any
+        // workspace is rearranged, since it may have moved to any of them
+        let mut taken = HashSet::new();
+        for (_, _, arrangement) in &rearrangements {
+            for hwnd in arrangement.hwnds() {
+                if let Some((monitor_idx, workspace_idx)) = self.window_location(hwnd) {
+                    self.monitors_mut()[monitor_idx]
+                        .workspaces_mut()
+                        .get_mut(workspace_idx)
+                        .ok_or_else(|| anyhow!("there is no workspace"))?
+                        .remove_window(hwnd)?;
+
+                    taken.insert(hwnd);
+                }
+            }
+        }
+
+        for (monitor_idx, workspace_idx, arrangement) in rearrangements {
+            let monitor = &mut self.monitors_mut()[monitor_idx];
+            monitor.ensure_workspace_count(workspace_idx + 1);
+
+            let workspace = monitor
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+            arrangement.apply(workspace, &taken)?;
+        }
+
+        for inverse in entry {
+            match inverse {
+                Inverse::Rearrange { .. } => {}
+                Inverse::FocusWorkspace {
+                    device_key,
+                    workspace_idx,
+                } => {
+                    if let Some(idx) = resolve(self, device_key) {
+                        let monitor = &mut self.monitors_mut()[idx];
+                        if *workspace_idx < monitor.workspaces().len() {
+                            monitor.focus_workspace(*workspace_idx)?;
+                        }
+                    }
+                }
+                Inverse::FocusMonitor { device_key } => {
+                    if let Some(idx) = resolve(self, device_key) {
+                        self.focus_monitor(idx)?;
+                    }
+                }
+            }
+        }
+
+        self.reload_workspaces()?;
+
+        Ok(before.inverse_from(&Arrangement::from(&*self)))
+    }
+}
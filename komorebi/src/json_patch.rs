@@ -0,0 +1,317 @@
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+/// A subset of the RFC 6902 JSON Patch operations; this is all that is needed to
+/// describe the difference between two komorebi states
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Compute the JSON Patch which transforms `from` into `to`
+#[must_use]
+pub fn diff(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut patch = vec![];
+    diff_value(&mut patch, "", from, to);
+    patch
+}
+
+fn diff_value(patch: &mut Vec<PatchOperation>, path: &str, from: &Value, to: &Value) {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            for (key, value) in from {
+                let path = format!("{path}/{}", escape(key));
+                match to.get(key) {
+                    None => patch.push(PatchOperation::Remove { path }),
+                    Some(target) => diff_value(patch, &path, value, target),
+                }
+            }
+
+            for (key, value) in to {
+                if !from.contains_key(key) {
+                    patch.push(PatchOperation::Add {
+                        path: format!("{path}/{}", escape(key)),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            let common = from.len().min(to.len());
+
+            for i in 0..common {
+                diff_value(patch, &format!("{path}/{i}"), &from[i], &to[i]);
+            }
+
+            // Remove trailing elements from the back so that the indices stay valid
+            for i in (common..from.len()).rev() {
+                patch.push(PatchOperation::Remove {
+                    path: format!("{path}/{i}"),
+                });
+            }
+
+            for (i, value) in to.iter().enumerate().skip(common) {
+                patch.push(PatchOperation::Add {
+                    path: format!("{path}/{i}"),
+                    value: value.clone(),
+                });
+            }
+        }
+        (from, to) => {
+            if from != to {
+                patch.push(PatchOperation::Replace {
+                    path: path.to_string(),
+                    value: to.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Apply a JSON Patch to a document in place
+pub fn apply(document: &mut Value, patch: &[PatchOperation]) -> Result<()> {
+    for operation in patch {
+        match operation {
+            PatchOperation::Add { path, value } => {
+                let (parent, token) = split(path)?;
+                match resolve(document, parent)? {
+                    Value::Object(object) => {
+                        object.insert(token, value.clone());
+                    }
+                    Value::Array(array) => {
+                        if token == "-" {
+                            array.push(value.clone());
+                        } else {
+                            let idx = index(&token, array.len() + 1)?;
+                            array.insert(idx, value.clone());
+                        }
+                    }
+                    _ => return Err(anyhow!("cannot add a value at {path}")),
+                }
+            }
+            PatchOperation::Remove { path } => {
+                let (parent, token) = split(path)?;
+                match resolve(document, parent)? {
+                    Value::Object(object) => {
+                        object
+                            .remove(&token)
+                            .ok_or_else(|| anyhow!("there is no value at {path}"))?;
+                    }
+                    Value::Array(array) => {
+                        let idx = index(&token, array.len())?;
+                        array.remove(idx);
+                    }
+                    _ => return Err(anyhow!("cannot remove a value at {path}")),
+                }
+            }
+            PatchOperation::Replace { path, value } => {
+                *resolve(document, path)? = value.clone();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn resolve<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Value> {
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| anyhow!("there is no value at {path}"))
+}
+
+fn split(path: &str) -> Result<(&str, String)> {
+    let (parent, token) = path
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("invalid json pointer: {path}"))?;
+
+    Ok((parent, unescape(token)))
+}
+
+fn index(token: &str, len: usize) -> Result<usize> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|idx| *idx < len)
+        .ok_or_else(|| anyhow!("invalid array index: {token}"))
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(from: &Value, to: &Value) -> Vec<PatchOperation> {
+        let patch = diff(from, to);
+        let mut document = from.clone();
+        apply(&mut document, &patch).unwrap();
+        assert_eq!(&document, to);
+        patch
+    }
+
+    #[test]
+    fn identical_documents_have_an_empty_patch() {
+        let document = json!({ "monitors": [{ "name": "DISPLAY1" }] });
+        assert!(diff(&document, &document).is_empty());
+    }
+
+    #[test]
+    fn changed_values_are_replaced() {
+        let patch = round_trip(
+            &json!({ "title": "a", "size": [1, 2] }),
+            &json!({ "title": "b", "size": [1, 3] }),
+        );
+
+        assert_eq!(patch.len(), 2);
+        assert!(patch.contains(&PatchOperation::Replace {
+            path: String::from("/size/1"),
+            value: json!(3),
+        }));
+        assert!(patch.contains(&PatchOperation::Replace {
+            path: String::from("/title"),
+            value: json!("b"),
+        }));
+    }
+
+    #[test]
+    fn object_keys_are_added_and_removed() {
+        let patch = round_trip(&json!({ "a": 1, "b": 2 }), &json!({ "b": 2, "c": 3 }));
+
+        assert_eq!(
+            patch,
+            vec![
+                PatchOperation::Remove {
+                    path: String::from("/a"),
+                },
+                PatchOperation::Add {
+                    path: String::from("/c"),
+                    value: json!(3),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn trailing_array_elements_are_removed_from_the_back() {
+        let patch = round_trip(&json!([1, 2, 3, 4]), &json!([1]));
+
+        assert_eq!(
+            patch,
+            vec![
+                PatchOperation::Remove {
+                    path: String::from("/3"),
+                },
+                PatchOperation::Remove {
+                    path: String::from("/2"),
+                },
+                PatchOperation::Remove {
+                    path: String::from("/1"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn array_elements_are_appended() {
+        round_trip(
+            &json!({ "windows": [] }),
+            &json!({ "windows": [{ "hwnd": 1 }, 2] }),
+        );
+    }
+
+    #[test]
+    fn keys_with_special_characters_are_escaped() {
+        let patch = round_trip(&json!({}), &json!({ "a/b~c": true }));
+
+        assert_eq!(
+            patch,
+            vec![PatchOperation::Add {
+                path: String::from("/a~1b~0c"),
+                value: json!(true),
+            }]
+        );
+    }
+
+    #[test]
+    fn nested_changes_round_trip() {
+        round_trip(
+            &json!({
+                "monitors": { "elements": [
+                    { "workspaces": { "elements": [{ "name": "I", "containers": [1, 2] }] } },
+                    { "workspaces": { "elements": [] } }
+                ], "focused": 0 }
+            }),
+            &json!({
+                "monitors": { "elements": [
+                    { "workspaces": { "elements": [{ "name": null, "containers": [2] }] } }
+                ], "focused": 0, "extra": "value" }
+            }),
+        );
+    }
+
+    #[test]
+    fn the_whole_document_can_be_replaced() {
+        round_trip(&json!([1, 2]), &json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn applying_to_a_missing_path_fails() {
+        let mut document = json!({ "a": 1 });
+
+        assert!(apply(
+            &mut document,
+            &[PatchOperation::Remove {
+                path: String::from("/b"),
+            }]
+        )
+        .is_err());
+
+        assert!(apply(
+            &mut document,
+            &[PatchOperation::Replace {
+                path: String::from("/b/c"),
+                value: json!(1),
+            }]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn array_indices_are_bounds_checked() {
+        let mut document = json!([1, 2]);
+
+        assert!(apply(
+            &mut document,
+            &[PatchOperation::Add {
+                path: String::from("/5"),
+                value: json!(3),
+            }]
+        )
+        .is_err());
+
+        apply(
+            &mut document,
+            &[PatchOperation::Add {
+                path: String::from("/-"),
+                value: json!(3),
+            }],
+        )
+        .unwrap();
+
+        assert_eq!(document, json!([1, 2, 3]));
+    }
+}
@@ -3,29 +3,47 @@ pub mod com;
 #[macro_use]
 pub mod ring;
 pub mod colour;
+pub mod command_macros;
 pub mod container;
+pub mod focus_history;
 pub mod hidden;
+pub mod journal;
+pub mod json_patch;
 pub mod monitor;
+pub mod monitor_navigation;
+pub mod notification_dispatcher;
+pub mod placement;
 pub mod process_command;
 pub mod process_event;
 pub mod process_movement;
+pub mod scratchpad;
+pub mod snapshots;
+pub mod sticky;
+pub mod tags;
 pub mod utils;
+pub mod virtual_monitors;
+pub mod websocket;
 pub mod static_config;
 pub mod styles;
 pub mod window;
 pub mod window_manager;
 pub mod window_manager_event;
+pub mod window_marks;
+pub mod window_search;
+pub mod window_selector;
 pub mod windows_api;
 pub mod windows_callbacks;
 pub mod winevent;
 pub mod winevent_listener;
 pub mod workspace;
+pub mod workspace_groups;
 pub mod gui_library;
 
 use lazy_static::lazy_static;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Write;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
 use std::net::TcpStream;
 use std::path::PathBuf;
 use std::process::Command;
@@ -34,8 +52,10 @@ use std::sync::atomic::AtomicI32;
 use std::sync::atomic::AtomicIsize;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Instant;
 
 pub use hidden::*;
 pub use process_command::*;
@@ -51,21 +71,28 @@ use komorebi_core::config_generation::IdWithIdentifier;
 use komorebi_core::config_generation::MatchingStrategy;
 use komorebi_core::ApplicationIdentifier;
 use komorebi_core::HidingBehaviour;
+use komorebi_core::OverflowPolicy;
 use komorebi_core::Rect;
 use komorebi_core::SocketMessage;
+use komorebi_core::StickyScope;
 use os_info::Version;
 use parking_lot::Mutex;
 use regex::Regex;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use uds_windows::UnixStream;
 use which::which;
 use winreg::enums::HKEY_CURRENT_USER;
 use winreg::RegKey;
 
+use crate::focus_history::FocusHistory;
+use crate::monitor::Monitor;
+
 type WorkspaceRule = (usize, usize, bool);
 
+// Applied once to the first new window matching the identifier, if it appears before the deadline
+type LaunchWorkspaceRule = (IdWithIdentifier, usize, usize, Instant);
+
 lazy_static! {
     static ref HIDDEN_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
     static ref MAXIMIZED_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
@@ -127,6 +154,17 @@ lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
     static ref WORKSPACE_RULES: Arc<Mutex<HashMap<String, WorkspaceRule>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    static ref WINDOW_LAST_FOCUSED: Arc<Mutex<HashMap<isize, u64>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref FOCUS_HISTORY: Arc<Mutex<FocusHistory>> =
+        Arc::new(Mutex::new(FocusHistory::default()));
+    static ref WINDOW_TAGS: Arc<Mutex<HashMap<isize, BTreeSet<String>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref WINDOW_MARKS: Arc<Mutex<HashMap<String, isize>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref WORKSPACE_HISTORY: Arc<Mutex<Vec<(usize, usize)>>> = Arc::new(Mutex::new(vec![]));
+    static ref LAUNCH_WORKSPACE_RULES: Arc<Mutex<Vec<LaunchWorkspaceRule>>> =
+        Arc::new(Mutex::new(vec![]));
     static ref REGEX_IDENTIFIERS: Arc<Mutex<HashMap<String, Regex>>> =
         Arc::new(Mutex::new(HashMap::new()));
     static ref MANAGE_IDENTIFIERS: Arc<Mutex<Vec<IdWithIdentifier>>> = Arc::new(Mutex::new(vec![]));
@@ -163,11 +201,35 @@ lazy_static! {
         "X410.exe".to_string(),
         "vcxsrv.exe".to_string(),
     ]));
-    static ref SUBSCRIPTION_PIPES: Arc<Mutex<HashMap<String, File>>> =
+    static ref SUBSCRIPTION_OVERFLOW_POLICY: Arc<Mutex<OverflowPolicy>> =
+        Arc::new(Mutex::new(OverflowPolicy::DropOldest));
+    static ref TCP_CONNECTIONS: Arc<Mutex<HashMap<String, TcpStream>>> =
         Arc::new(Mutex::new(HashMap::new()));
-    static ref SUBSCRIPTION_SOCKETS: Arc<Mutex<HashMap<String, PathBuf>>> =
+    pub static ref TCP_BIND_ADDRESS: Arc<Mutex<IpAddr>> =
+        Arc::new(Mutex::new(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    static ref TCP_TOKENS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    static ref TCP_READ_ONLY_TOKENS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    static ref WEBSOCKET_ALLOWED_ORIGINS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    static ref MACROS: Arc<Mutex<HashMap<String, Vec<serde_json::Value>>>> =
         Arc::new(Mutex::new(HashMap::new()));
-    static ref TCP_CONNECTIONS: Arc<Mutex<HashMap<String, TcpStream>>> =
+    static ref MONITOR_CONFIGURATIONS: Arc<Mutex<Vec<MonitorConfig>>> =
+        Arc::new(Mutex::new(vec![]));
+    static ref DEFAULT_MONITOR_CONFIGURATION: Arc<Mutex<Option<MonitorConfig>>> =
+        Arc::new(Mutex::new(None));
+    static ref MONITOR_ADJACENCY: Arc<Mutex<HashMap<String, MonitorNeighbours>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref WINDOW_PLACEMENT_TOLERANCE: Arc<Mutex<f64>> = Arc::new(Mutex::new(0.5));
+    static ref WORKSPACE_GROUPS: Arc<Mutex<HashMap<String, HashMap<String, WorkspaceReference>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref STICKY_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
+    static ref STICKY_IDENTIFIERS: Arc<Mutex<Vec<IdWithIdentifier>>> = Arc::new(Mutex::new(vec![]));
+    static ref STICKY_RULE_SCOPE: Arc<Mutex<StickyScope>> =
+        Arc::new(Mutex::new(StickyScope::Monitor));
+    static ref SCRATCHPAD_IDENTIFIERS: Arc<Mutex<HashMap<String, IdWithIdentifier>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref SCRATCHPAD_WINDOWS: Arc<Mutex<HashMap<String, isize>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref LAUNCH_COMMANDS: Arc<Mutex<HashMap<String, String>>> =
         Arc::new(Mutex::new(HashMap::new()));
     static ref HIDING_BEHAVIOUR: Arc<Mutex<HidingBehaviour>> =
         Arc::new(Mutex::new(HidingBehaviour::Minimize));
@@ -215,6 +277,13 @@ lazy_static! {
 pub static DEFAULT_WORKSPACE_PADDING: AtomicI32 = AtomicI32::new(10);
 pub static DEFAULT_CONTAINER_PADDING: AtomicI32 = AtomicI32::new(10);
 
+pub static SUBSCRIPTION_QUEUE_CAPACITY: AtomicUsize = AtomicUsize::new(64);
+
+pub static HISTORY_DEPTH: AtomicUsize = AtomicUsize::new(20);
+pub static MONITOR_NAVIGATION_WRAP: AtomicBool = AtomicBool::new(false);
+pub static WINDOW_PLACEMENT_ENABLED: AtomicBool = AtomicBool::new(true);
+pub static WINDOW_PLACEMENT_INTERVAL: AtomicU64 = AtomicU64::new(30);
+
 pub static NATIVE_ANIMATION_DELAY: AtomicU64 = AtomicU64::new(35);
 pub static FINISH_MINIMIZE_ANIMATION: AtomicBool = AtomicBool::new(true);
 
@@ -286,67 +355,72 @@ pub fn current_virtual_desktop() -> Option<Vec<u8>> {
 pub enum NotificationEvent {
     WindowManager(WindowManagerEvent),
     Socket(SocketMessage),
+    Monitor(MonitorNotification),
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct Notification {
-    pub event: NotificationEvent,
-    pub state: State,
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "content")]
+pub enum MonitorNotification {
+    MonitorAdded(MonitorDetails),
+    MonitorRemoved(MonitorDetails),
 }
 
-pub fn notify_subscribers(notification: &str) -> Result<()> {
-    let mut stale_sockets = vec![];
-    let mut sockets = SUBSCRIPTION_SOCKETS.lock();
-
-    for (socket, path) in &mut *sockets {
-        match UnixStream::connect(path) {
-            Ok(mut stream) => {
-                tracing::debug!("pushed notification to subscriber: {socket}");
-                stream.write_all(notification.as_bytes())?;
-            }
-            Err(_) => {
-                stale_sockets.push(socket.clone());
-            }
-        }
-    }
-
-    for socket in stale_sockets {
-        tracing::warn!("removing stale subscription: {socket}");
-        sockets.remove(&socket);
-    }
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MonitorDetails {
+    /// The index the monitor was added at or removed from
+    pub index: usize,
+    pub name: String,
+    pub device_id: Option<String>,
+}
 
-    let mut stale_pipes = vec![];
-    let mut pipes = SUBSCRIPTION_PIPES.lock();
-    for (subscriber, pipe) in &mut *pipes {
-        match writeln!(pipe, "{notification}") {
-            Ok(()) => {
-                tracing::debug!("pushed notification to subscriber: {subscriber}");
-            }
-            Err(error) => {
-                // ERROR_FILE_NOT_FOUND
-                // 2 (0x2)
-                // The system cannot find the file specified.
-
-                // ERROR_NO_DATA
-                // 232 (0xE8)
-                // The pipe is being closed.
-
-                // Remove the subscription; the process will have to subscribe again
-                if let Some(2 | 232) = error.raw_os_error() {
-                    stale_pipes.push(subscriber.clone());
-                }
-            }
+impl MonitorDetails {
+    pub fn from_monitor(index: usize, monitor: &Monitor) -> Self {
+        Self {
+            index,
+            name: monitor.name().clone(),
+            device_id: monitor.device_id().clone(),
         }
     }
+}
 
-    for subscriber in stale_pipes {
-        tracing::warn!("removing stale subscription: {}", subscriber);
-        pipes.remove(&subscriber);
-    }
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Notification {
+    pub event: NotificationEvent,
+    pub state: State,
+}
 
+/// The payload sent to subscribers which registered with [`komorebi_core::SubscriptionMode::Delta`]
+///
+/// The sequence number is incremented by one for every payload sent to a subscriber; if a client
+/// observes a gap, it should send `SocketMessage::ResyncSubscriber` to receive a fresh snapshot
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum DeltaNotification {
+    Snapshot {
+        sequence: u64,
+        notification: serde_json::Value,
+    },
+    Patch {
+        sequence: u64,
+        patch: Vec<json_patch::PatchOperation>,
+    },
+}
+
+/// Queue a notification for delivery to all subscribers; this never blocks on a subscriber
+pub fn notify_subscribers(notification: &str) -> Result<()> {
+    notification_dispatcher::notification_tx().send(notification.to_string())?;
     Ok(())
 }
 
+/// Forget everything kept about a window outside of the workspaces once it has been destroyed or
+/// unmanaged
+pub fn forget_window(hwnd: isize) {
+    focus_history::forget_focus(hwnd);
+    window_marks::forget_window(hwnd);
+    scratchpad::forget_window(hwnd);
+    tags::forget_window(hwnd);
+}
+
 pub fn load_configuration() -> Result<()> {
     let config_pwsh = HOME_DIR.join("komorebi.ps1");
     let config_ahk = HOME_DIR.join("komorebi.ahk");
@@ -6,6 +6,7 @@
     clippy::significant_drop_in_scrutinee
 )]
 
+use std::net::IpAddr;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -25,11 +26,14 @@ use tracing_subscriber::EnvFilter;
 
 use komorebi::hidden::Hidden;
 use komorebi::load_configuration;
+use komorebi::notification_dispatcher;
+use komorebi::placement;
 use komorebi::process_command::listen_for_commands;
 use komorebi::process_command::listen_for_commands_tcp;
 use komorebi::process_event::listen_for_events;
 use komorebi::process_movement::listen_for_movements;
 use komorebi::static_config::StaticConfig;
+use komorebi::websocket::listen_for_commands_ws;
 use komorebi::window_manager::WindowManager;
 use komorebi::windows_api::WindowsApi;
 use komorebi::winevent_listener;
@@ -37,6 +41,8 @@ use komorebi::CUSTOM_FFM;
 use komorebi::HOME_DIR;
 use komorebi::INITIAL_CONFIGURATION_LOADED;
 use komorebi::SESSION_ID;
+use komorebi::TCP_BIND_ADDRESS;
+use komorebi::WINDOW_PLACEMENT_ENABLED;
 
 fn setup() -> Result<(WorkerGuard, WorkerGuard)> {
     if std::env::var("RUST_LIB_BACKTRACE").is_err() {
@@ -136,6 +142,12 @@ struct Opts {
     /// Start a TCP server on the given port to allow the direct sending of SocketMessages
     #[clap(short, long)]
     tcp_port: Option<usize>,
+    /// Address to bind the TCP server to (default: 127.0.0.1, or tcp.bind_address in the static config)
+    #[clap(long)]
+    tcp_bind_address: Option<IpAddr>,
+    /// Start a WebSocket server on the given localhost port to allow the sending of SocketMessages and receiving of notifications
+    #[clap(long)]
+    ws_port: Option<usize>,
     /// Path to a static configuration JSON file
     #[clap(short, long)]
     config: Option<PathBuf>,
@@ -181,6 +193,7 @@ fn main() -> Result<()> {
     WindowsApi::foreground_lock_timeout()?;
 
     winevent_listener::start();
+    notification_dispatcher::start();
 
     #[cfg(feature = "deadlock_detection")]
     detect_deadlocks();
@@ -228,7 +241,15 @@ fn main() -> Result<()> {
     };
 
     if let Some(port) = opts.tcp_port {
-        listen_for_commands_tcp(wm.clone(), port);
+        let bind_address = opts
+            .tcp_bind_address
+            .unwrap_or_else(|| *TCP_BIND_ADDRESS.lock());
+
+        listen_for_commands_tcp(wm.clone(), bind_address, port);
+    }
+
+    if let Some(port) = opts.ws_port {
+        listen_for_commands_ws(wm.clone(), port);
     }
 
     if static_config.is_none() {
@@ -244,7 +265,14 @@ fn main() -> Result<()> {
 
     wm.lock().retile_all(false)?;
 
+    if WINDOW_PLACEMENT_ENABLED.load(Ordering::SeqCst) {
+        if let Err(error) = wm.lock().restore_placement() {
+            tracing::warn!("could not restore window placement: {error}");
+        }
+    }
+
     listen_for_events(wm.clone());
+    placement::persist_periodically(wm.clone());
 
     if CUSTOM_FFM.load(Ordering::SeqCst) {
         listen_for_movements(wm.clone());
@@ -263,6 +291,12 @@ fn main() -> Result<()> {
 
     tracing::error!("received ctrl-c, restoring all hidden windows and terminating process");
 
+    if WINDOW_PLACEMENT_ENABLED.load(Ordering::SeqCst) {
+        if let Err(error) = wm.lock().persist_placement() {
+            tracing::warn!("could not persist window placement: {error}");
+        }
+    }
+
     wm.lock().restore_all_windows()?;
 
     if WindowsApi::focus_follows_mouse()? {
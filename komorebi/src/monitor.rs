@@ -19,6 +19,10 @@ use komorebi_core::Rect;
 
 use crate::container::Container;
 use crate::ring::Ring;
+use crate::static_config::MonitorConfig;
+use crate::static_config::WorkspaceConfig;
+use crate::sticky::StickyWindow;
+use crate::tags::TagView;
 use crate::workspace::Workspace;
 use crate::HIDING_BEHAVIOUR;
 use crate::FINISH_MINIMIZE_ANIMATION;
@@ -45,9 +49,58 @@ pub struct Monitor {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[getset(get_copy = "pub", set = "pub")]
     last_focused_workspace: Option<usize>,
-    #[getset(get_mut = "pub")]
+    #[getset(get = "pub", get_mut = "pub")]
     workspace_names: HashMap<usize, String>,
     wait_for_minimization: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    virtual_split: Option<VirtualSplit>,
+    #[getset(get_copy = "pub", set = "pub")]
+    dynamic_workspaces: bool,
+    #[getset(get = "pub", get_mut = "pub")]
+    sticky_windows: Vec<StickyWindow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub", set = "pub")]
+    tag_view: Option<TagView>,
+}
+
+/// One part of a physical display which has been split into side-by-side virtual monitors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VirtualSplit {
+    /// Position of this part, counting from the left
+    pub part: usize,
+    /// Relative widths of all of the parts of the display
+    pub widths: Vec<u32>,
+}
+
+impl VirtualSplit {
+    /// The slice of a rectangle of the physical display which this part covers
+    pub fn slice(&self, rect: &Rect) -> Rect {
+        let total = self
+            .widths
+            .iter()
+            .map(|w| i64::from(*w))
+            .sum::<i64>()
+            .max(1);
+        let before = self
+            .widths
+            .iter()
+            .take(self.part)
+            .map(|w| i64::from(*w))
+            .sum::<i64>();
+        let width = i64::from(self.widths.get(self.part).copied().unwrap_or(0));
+
+        let full = i64::from(rect.right);
+        let left = full * before / total;
+        let right = full * (before + width) / total;
+
+        Rect {
+            left: rect.left + i32::try_from(left).unwrap_or_default(),
+            top: rect.top,
+            right: i32::try_from(right - left).unwrap_or_default(),
+            bottom: rect.bottom,
+        }
+    }
 }
 
 impl_ring_elements!(Monitor, Workspace);
@@ -68,25 +121,57 @@ pub fn new(id: isize, size: Rect, work_area_size: Rect, name: String) -> Monitor
         last_focused_workspace: None,
         workspace_names: HashMap::default(),
         wait_for_minimization: FINISH_MINIMIZE_ANIMATION.load(Ordering::SeqCst),
+        virtual_split: None,
+        dynamic_workspaces: false,
+        sticky_windows: vec![],
+        tag_view: None,
     }
 }
 
 impl Monitor {
+    /// Identifies the physical display across disconnections and reconnections, unlike its index
+    /// or its handle
+    pub fn device_key(&self) -> String {
+        let key = self
+            .device_id()
+            .clone()
+            .unwrap_or_else(|| self.name().clone());
+
+        match self.virtual_split() {
+            None => key,
+            Some(split) => format!("{key}#{}", split.part),
+        }
+    }
+
+    /// Whether this is a whole display or the first part of a split display
+    pub fn is_physical(&self) -> bool {
+        self.virtual_split()
+            .as_ref()
+            .map_or(true, |split| split.part == 0)
+    }
+
+    pub fn load_static_config(&mut self, config: &MonitorConfig) -> Result<()> {
+        self.set_work_area_offset(config.work_area_offset);
+        self.set_dynamic_workspaces(config.dynamic_workspaces.unwrap_or_default());
+        self.load_workspace_configs(&config.workspaces)
+    }
+
+    pub fn load_workspace_configs(&mut self, configs: &[WorkspaceConfig]) -> Result<()> {
+        self.ensure_workspace_count(configs.len());
+
+        for (workspace, workspace_config) in self.workspaces_mut().iter_mut().zip(configs) {
+            workspace.load_static_config(workspace_config)?;
+        }
+
+        Ok(())
+    }
+
     pub fn load_focused_workspace(
         &mut self,
         mouse_follows_focus: bool,
     ) -> Result<()> {
-        let focused_idx = self.focused_workspace_idx();
-        for (i, workspace) in self.workspaces_mut().iter_mut().enumerate() {
-            if i != focused_idx {
-                workspace.hide(None)?;
-            }
-        }
-
-        if let HidingBehaviour::Minimize = *HIDING_BEHAVIOUR.lock() {
-            if self.wait_for_minimization {
-                sleep(Duration::from_millis(200));
-            }
+        if self.hide_unfocused_workspaces()? {
+            sleep(Duration::from_millis(200));
         }
 
         if let Some(workspace) = self.focused_workspace_mut() {
@@ -96,6 +181,22 @@ impl Monitor {
         Ok(())
     }
 
+    /// Hide every workspace other than the focused one, returning whether the minimize animations
+    /// should be waited for before restoring the focused workspace
+    pub fn hide_unfocused_workspaces(&mut self) -> Result<bool> {
+        let focused_idx = self.focused_workspace_idx();
+        for (i, workspace) in self.workspaces_mut().iter_mut().enumerate() {
+            if i != focused_idx {
+                workspace.hide(None)?;
+            }
+        }
+
+        Ok(
+            matches!(*HIDING_BEHAVIOUR.lock(), HidingBehaviour::Minimize)
+                && self.wait_for_minimization,
+        )
+    }
+
     pub fn add_container(
         &mut self,
         container: Container,
@@ -136,6 +237,76 @@ impl Monitor {
         }
     }
 
+    /// Whether a workspace can be removed automatically in dynamic mode; workspaces with a name
+    /// are pinned, as is the focused workspace
+    fn is_disposable_workspace(&self, idx: usize) -> bool {
+        idx != self.focused_workspace_idx()
+            && !self
+                .tag_view
+                .as_ref()
+                .is_some_and(|view| view.involves(idx))
+            && self
+                .workspaces()
+                .get(idx)
+                .is_some_and(|workspace| workspace.name().is_none() && workspace.is_empty())
+    }
+
+    /// In dynamic mode, remove the empty workspaces which are not focused and make sure that there
+    /// is an empty workspace at the end, returning the indices of the removed workspaces from
+    /// highest to lowest
+    pub fn update_dynamic_workspaces(&mut self) -> Vec<usize> {
+        let mut removed = vec![];
+
+        if !self.dynamic_workspaces {
+            return removed;
+        }
+
+        for idx in (0..self.workspaces().len()).rev() {
+            if !self.is_disposable_workspace(idx) {
+                continue;
+            }
+
+            let focused_idx = self.focused_workspace_idx();
+            if self.remove_workspace_by_idx(idx).is_none() {
+                continue;
+            }
+
+            if idx < focused_idx {
+                self.workspaces.focus(focused_idx - 1);
+            }
+
+            if let Some(view) = &mut self.tag_view {
+                view.forget_workspace(idx);
+            }
+
+            self.last_focused_workspace = match self.last_focused_workspace {
+                Some(last) if last == idx => None,
+                Some(last) if last > idx => Option::from(last - 1),
+                last => last,
+            };
+
+            self.workspace_names = self
+                .workspace_names
+                .drain()
+                .filter(|(i, _)| *i != idx)
+                .map(|(i, name)| if i > idx { (i - 1, name) } else { (i, name) })
+                .collect();
+
+            removed.push(idx);
+        }
+
+        let has_trailing_empty = self
+            .workspaces()
+            .back()
+            .is_some_and(|workspace| workspace.name().is_none() && workspace.is_empty());
+
+        if !has_trailing_empty {
+            self.workspaces_mut().push_back(Workspace::default());
+        }
+
+        removed
+    }
+
     pub fn remove_workspaces(&mut self) -> VecDeque<Workspace> {
         self.workspaces_mut().drain(..).collect()
     }
@@ -187,3 +358,73 @@ impl Monitor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, right: i32) -> Rect {
+        Rect {
+            left,
+            top: 40,
+            right,
+            bottom: 1040,
+        }
+    }
+
+    fn slices(widths: &[u32], display: &Rect) -> Vec<Rect> {
+        (0..widths.len())
+            .map(|part| {
+                VirtualSplit {
+                    part,
+                    widths: widths.to_vec(),
+                }
+                .slice(display)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slice_equal_widths() {
+        assert_eq!(
+            slices(&[1, 1], &rect(0, 1920)),
+            vec![rect(0, 960), rect(960, 960)]
+        );
+    }
+
+    #[test]
+    fn slice_relative_widths_from_display_left() {
+        assert_eq!(
+            slices(&[1, 2], &rect(1920, 3000)),
+            vec![rect(1920, 1000), rect(2920, 2000)]
+        );
+    }
+
+    #[test]
+    fn slices_cover_the_display_without_gaps() {
+        let display = rect(-1000, 1000);
+        let parts = slices(&[1, 1, 1], &display);
+
+        assert_eq!(
+            parts,
+            vec![rect(-1000, 333), rect(-667, 333), rect(-334, 334)]
+        );
+
+        for pair in parts.windows(2) {
+            assert_eq!(pair[0].left + pair[0].right, pair[1].left);
+        }
+
+        let last = parts.last().unwrap();
+        assert_eq!(last.left + last.right, display.left + display.right);
+    }
+
+    #[test]
+    fn slice_of_missing_part_is_empty() {
+        let split = VirtualSplit {
+            part: 2,
+            widths: vec![1, 1],
+        };
+
+        assert_eq!(split.slice(&rect(0, 1920)), rect(1920, 0));
+    }
+}
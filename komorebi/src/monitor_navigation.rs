@@ -0,0 +1,218 @@
+use std::sync::atomic::Ordering;
+
+use komorebi_core::OperationDirection;
+use komorebi_core::Rect;
+
+use crate::window_manager::WindowManager;
+use crate::MONITOR_ADJACENCY;
+use crate::MONITOR_NAVIGATION_WRAP;
+
+/// The start and end of a rectangle along the axis of a direction, and across it
+const fn spans(rect: &Rect, direction: OperationDirection) -> ((i32, i32), (i32, i32)) {
+    let horizontal = (rect.left, rect.left + rect.right);
+    let vertical = (rect.top, rect.top + rect.bottom);
+
+    match direction {
+        OperationDirection::Left | OperationDirection::Right => (horizontal, vertical),
+        OperationDirection::Up | OperationDirection::Down => (vertical, horizontal),
+    }
+}
+
+const fn sign(direction: OperationDirection) -> i32 {
+    match direction {
+        OperationDirection::Left | OperationDirection::Up => -1,
+        OperationDirection::Right | OperationDirection::Down => 1,
+    }
+}
+
+/// Twice the centre of a span, which avoids rounding
+const fn doubled_centre(span: (i32, i32)) -> i32 {
+    span.0 + span.1
+}
+
+/// How a monitor lies relative to the current one when looking in a direction
+struct Placement {
+    /// Signed distance between the centres along the direction, doubled
+    offset: i32,
+    /// Gap between the facing edges along the direction, zero if they touch or overlap
+    gap: i32,
+    /// Length of the overlap across the direction, zero or negative if there is none
+    overlap: i32,
+    /// Distance between the centres across the direction, doubled
+    misalignment: i32,
+}
+
+impl Placement {
+    fn new(current: &Rect, candidate: &Rect, direction: OperationDirection) -> Self {
+        let (current_along, current_across) = spans(current, direction);
+        let (candidate_along, candidate_across) = spans(candidate, direction);
+        let sign = sign(direction);
+
+        let gap = if sign > 0 {
+            candidate_along.0 - current_along.1
+        } else {
+            current_along.0 - candidate_along.1
+        };
+
+        Self {
+            offset: sign * (doubled_centre(candidate_along) - doubled_centre(current_along)),
+            gap: gap.max(0),
+            overlap: current_across.1.min(candidate_across.1)
+                - current_across.0.max(candidate_across.0),
+            misalignment: (doubled_centre(candidate_across) - doubled_centre(current_across)).abs(),
+        }
+    }
+}
+
+impl WindowManager {
+    /// The monitor with a device key; a display which has been split is found by the key it had
+    /// before, as its first virtual monitor
+    pub fn monitor_idx_for_device_key(&self, key: &str) -> Option<usize> {
+        let monitors = self.monitors();
+
+        monitors
+            .iter()
+            .position(|monitor| monitor.device_key() == key)
+            .or_else(|| {
+                monitors.iter().position(|monitor| {
+                    monitor.is_physical()
+                        && monitor.virtual_split().is_some()
+                        && monitor
+                            .device_key()
+                            .rsplit_once('#')
+                            .is_some_and(|(physical, _)| physical == key)
+                })
+            })
+    }
+
+    /// The monitor in a direction from the focused monitor: an explicitly configured neighbour if
+    /// there is one, otherwise the closest monitor in that direction, preferring monitors which
+    /// overlap the focused monitor across the direction; if there is none and wrapping is
+    /// enabled, the furthest monitor in the opposite direction
+    pub fn monitor_idx_in_direction(&self, direction: OperationDirection) -> Option<usize> {
+        let current_idx = self.focused_monitor_idx();
+        let current_monitor_size = self.focused_monitor_size().ok()?;
+        let current_key = self.focused_monitor()?.device_key();
+
+        let configured = MONITOR_ADJACENCY
+            .lock()
+            .get(&current_key)
+            .and_then(|neighbours| neighbours.in_direction(direction))
+            .cloned();
+
+        if let Some(key) = configured {
+            if let Some(idx) = self.monitor_idx_for_device_key(&key) {
+                return Option::from(idx);
+            }
+
+            tracing::warn!("ignoring configured neighbour {key} which is not connected");
+        }
+
+        let placements = self
+            .monitors()
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != current_idx)
+            .map(|(idx, monitor)| {
+                (
+                    idx,
+                    Placement::new(&current_monitor_size, monitor.size(), direction),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let closest = placements
+            .iter()
+            .filter(|(_, placement)| placement.offset > 0)
+            .min_by_key(|(_, placement)| {
+                (
+                    placement.overlap <= 0,
+                    placement.gap,
+                    placement.misalignment,
+                )
+            })
+            .map(|(idx, _)| *idx);
+
+        if closest.is_some() || !MONITOR_NAVIGATION_WRAP.load(Ordering::SeqCst) {
+            return closest;
+        }
+
+        placements
+            .iter()
+            .filter(|(_, placement)| placement.offset < 0)
+            .min_by_key(|(_, placement)| {
+                (
+                    placement.overlap <= 0,
+                    placement.offset,
+                    placement.misalignment,
+                )
+            })
+            .map(|(idx, _)| *idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    const CURRENT: Rect = rect(0, 0, 1920, 1080);
+
+    fn placement(candidate: &Rect, direction: OperationDirection) -> (i32, i32, i32, i32) {
+        let placement = Placement::new(&CURRENT, candidate, direction);
+        (
+            placement.offset,
+            placement.gap,
+            placement.overlap,
+            placement.misalignment,
+        )
+    }
+
+    #[test]
+    fn adjacent_and_aligned() {
+        assert_eq!(
+            placement(&rect(1920, 0, 1920, 1080), OperationDirection::Right),
+            (3840, 0, 1080, 0)
+        );
+    }
+
+    #[test]
+    fn gap_between_edges() {
+        assert_eq!(
+            placement(&rect(-2000, 0, 1900, 1080), OperationDirection::Left),
+            (4020, 100, 1080, 0)
+        );
+    }
+
+    #[test]
+    fn vertical_offset_overlaps_partially() {
+        assert_eq!(
+            placement(&rect(1920, 540, 1920, 1080), OperationDirection::Right),
+            (3840, 0, 540, 1080)
+        );
+    }
+
+    #[test]
+    fn no_overlap_across_the_direction() {
+        assert_eq!(
+            placement(&rect(3000, -1080, 1000, 1080), OperationDirection::Up),
+            (2160, 0, -1080, 5080)
+        );
+    }
+
+    #[test]
+    fn monitor_in_the_opposite_direction() {
+        let (offset, gap, _, _) = placement(&rect(-1920, 0, 1920, 1080), OperationDirection::Right);
+
+        assert!(offset < 0);
+        assert_eq!(gap, 0);
+    }
+}
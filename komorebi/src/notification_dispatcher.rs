@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::os::windows::io::AsRawHandle;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use crossbeam_channel::Receiver;
+use crossbeam_channel::RecvTimeoutError;
+use crossbeam_channel::Sender;
+use crossbeam_channel::TrySendError;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use uds_windows::UnixStream;
+use windows::Win32::Foundation::HANDLE;
+
+use komorebi_core::OverflowPolicy;
+use komorebi_core::SubscriptionMode;
+
+use crate::json_patch;
+use crate::windows_api::WindowsApi;
+use crate::DeltaNotification;
+use crate::SUBSCRIPTION_OVERFLOW_POLICY;
+use crate::SUBSCRIPTION_QUEUE_CAPACITY;
+
+static CHANNEL: OnceLock<(Sender<String>, Receiver<String>)> = OnceLock::new();
+
+static DISPATCHER: OnceLock<JoinHandle<()>> = OnceLock::new();
+
+static NEXT_SUBSCRIBER_ID: AtomicU64 = AtomicU64::new(0);
+
+pub static DROPPED_NOTIFICATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A subscriber which has not accepted a notification within this time is disconnected, so
+/// that a hung subscriber does not keep its writer thread blocked forever
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref SUBSCRIBERS: Arc<Mutex<HashMap<String, Subscriber>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref PIPE_WRITERS: Arc<Mutex<Vec<PipeWriter>>> = Arc::new(Mutex::new(vec![]));
+}
+
+pub fn start() {
+    DISPATCHER.get_or_init(|| {
+        std::thread::spawn(move || {
+            tracing::info!("dispatching notifications");
+            loop {
+                match channel().1.recv_timeout(WRITE_TIMEOUT) {
+                    Ok(notification) => dispatch(&notification),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                cancel_hung_pipe_writes();
+            }
+        })
+    });
+}
+
+fn channel() -> &'static (Sender<String>, Receiver<String>) {
+    CHANNEL.get_or_init(crossbeam_channel::unbounded)
+}
+
+pub fn notification_tx() -> Sender<String> {
+    channel().0.clone()
+}
+
+#[must_use]
+pub fn socket_subscription_key(socket: &str) -> String {
+    format!("socket:{socket}")
+}
+
+#[must_use]
+pub fn stream_subscription_key(stream: &str) -> String {
+    format!("stream:{stream}")
+}
+
+#[must_use]
+pub fn websocket_subscription_key(websocket: &str) -> String {
+    format!("websocket:{websocket}")
+}
+
+#[must_use]
+pub fn pipe_subscription_key(pipe: &str) -> String {
+    format!("pipe:{pipe}")
+}
+
+pub enum Transport {
+    /// A new connection to the socket is made for every notification
+    Socket(PathBuf),
+    /// A named pipe which has been connected to by komorebi
+    Pipe(File),
+    /// A persistent connection made by the subscriber, which receives newline-delimited JSON
+    Stream(UnixStream),
+    /// A channel drained by a listener which manages its own connection, such as a WebSocket
+    Channel(Sender<String>),
+}
+
+impl Transport {
+    fn deliver(&mut self, payload: &str) -> std::io::Result<()> {
+        match self {
+            Self::Socket(path) => {
+                let mut stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(Option::from(WRITE_TIMEOUT))?;
+                stream.write_all(payload.as_bytes())
+            }
+            Self::Pipe(pipe) => writeln!(pipe, "{payload}"),
+            Self::Stream(stream) => writeln!(stream, "{payload}"),
+            Self::Channel(sender) => sender
+                .send_timeout(payload.to_string(), WRITE_TIMEOUT)
+                .map_err(|_| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::BrokenPipe,
+                        "the channel is closed or full",
+                    )
+                }),
+        }
+    }
+
+    fn is_stale(&self, error: &std::io::Error) -> bool {
+        match self {
+            Self::Socket(_) | Self::Stream(_) | Self::Channel(_) => true,
+            // ERROR_FILE_NOT_FOUND
+            // 2 (0x2)
+            // The system cannot find the file specified.
+
+            // ERROR_NO_DATA
+            // 232 (0xE8)
+            // The pipe is being closed.
+
+            // ERROR_OPERATION_ABORTED
+            // 995 (0x3E3)
+            // The I/O operation has been aborted because of either a thread exit or an application request.
+            Self::Pipe(_) => matches!(error.raw_os_error(), Some(2 | 232 | 995)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DeltaSubscription {
+    sequence: u64,
+    last: Option<serde_json::Value>,
+}
+
+impl DeltaSubscription {
+    fn next(&mut self, notification: &serde_json::Value) -> DeltaNotification {
+        self.sequence += 1;
+
+        let delta = self.last.as_ref().map_or_else(
+            || DeltaNotification::Snapshot {
+                sequence: self.sequence,
+                notification: notification.clone(),
+            },
+            |last| DeltaNotification::Patch {
+                sequence: self.sequence,
+                patch: json_patch::diff(last, notification),
+            },
+        );
+
+        self.last = Option::from(notification.clone());
+        delta
+    }
+}
+
+#[derive(Debug, Default)]
+struct SubscriberMetrics {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Writes to named pipes cannot time out, so the dispatcher cancels any which take too long
+struct PipeWriter {
+    thread: JoinHandle<()>,
+    writing_since: Arc<Mutex<Option<Instant>>>,
+}
+
+fn cancel_hung_pipe_writes() {
+    let mut writers = PIPE_WRITERS.lock();
+    writers.retain(|writer| !writer.thread.is_finished());
+
+    for writer in writers.iter() {
+        let is_hung = writer
+            .writing_since
+            .lock()
+            .is_some_and(|since| since.elapsed() > WRITE_TIMEOUT);
+
+        if is_hung {
+            tracing::warn!("cancelling a notification write which has timed out");
+            let thread = HANDLE(writer.thread.as_raw_handle() as isize);
+            if let Err(error) = WindowsApi::cancel_synchronous_io(thread) {
+                tracing::error!("could not cancel notification write: {error}");
+            }
+        }
+    }
+}
+
+struct Subscriber {
+    id: u64,
+    sender: Sender<String>,
+    // Kept so that the oldest queued notification can be evicted when the queue is full
+    receiver: Receiver<String>,
+    delta: Option<DeltaSubscription>,
+    metrics: Arc<SubscriberMetrics>,
+}
+
+impl Subscriber {
+    /// Returns false if the subscriber should be disconnected
+    fn enqueue(&self, key: &str, payload: String, policy: OverflowPolicy) -> bool {
+        match self.sender.try_send(payload) {
+            Ok(()) => true,
+            Err(TrySendError::Full(payload)) => {
+                self.metrics.dropped.fetch_add(1, Ordering::SeqCst);
+                DROPPED_NOTIFICATIONS.fetch_add(1, Ordering::SeqCst);
+
+                match policy {
+                    OverflowPolicy::DropOldest => {
+                        tracing::warn!("notification queue full, dropping oldest: {key}");
+                        let _ = self.receiver.try_recv();
+                        let _ = self.sender.try_send(payload);
+                        true
+                    }
+                    OverflowPolicy::Disconnect => {
+                        tracing::warn!("notification queue full, disconnecting: {key}");
+                        false
+                    }
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubscriberStats {
+    pub subscriber: String,
+    pub mode: SubscriptionMode,
+    pub queued: usize,
+    pub delivered: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubscriptionMetrics {
+    pub dropped: u64,
+    pub subscribers: Vec<SubscriberStats>,
+}
+
+/// Changes to [`SUBSCRIPTION_QUEUE_CAPACITY`] only apply to subscribers added afterwards; the
+/// queues of existing subscribers keep the capacity they were created with
+pub fn add_subscriber(key: String, transport: Transport, mode: SubscriptionMode) {
+    let capacity = SUBSCRIPTION_QUEUE_CAPACITY.load(Ordering::SeqCst).max(1);
+    let (sender, receiver) = crossbeam_channel::bounded(capacity);
+    let id = NEXT_SUBSCRIBER_ID.fetch_add(1, Ordering::SeqCst);
+    let metrics = Arc::new(SubscriberMetrics::default());
+
+    spawn_writer(
+        key.clone(),
+        id,
+        transport,
+        receiver.clone(),
+        metrics.clone(),
+    );
+
+    let mut subscribers = SUBSCRIBERS.lock();
+    subscribers.insert(
+        key,
+        Subscriber {
+            id,
+            sender,
+            receiver,
+            delta: match mode {
+                SubscriptionMode::Full => None,
+                SubscriptionMode::Delta => Option::from(DeltaSubscription::default()),
+            },
+            metrics,
+        },
+    );
+}
+
+pub fn remove_subscriber(key: &str) {
+    let mut subscribers = SUBSCRIBERS.lock();
+    subscribers.remove(key);
+}
+
+pub fn resync_subscriber(key: &str) {
+    let mut subscribers = SUBSCRIBERS.lock();
+    if let Some(delta) = subscribers.get_mut(key).and_then(|s| s.delta.as_mut()) {
+        delta.last = None;
+    }
+}
+
+#[must_use]
+pub fn subscription_metrics() -> SubscriptionMetrics {
+    let subscribers = SUBSCRIBERS.lock();
+
+    let mut stats = subscribers
+        .iter()
+        .map(|(key, subscriber)| SubscriberStats {
+            subscriber: key.clone(),
+            mode: if subscriber.delta.is_some() {
+                SubscriptionMode::Delta
+            } else {
+                SubscriptionMode::Full
+            },
+            queued: subscriber.sender.len(),
+            delivered: subscriber.metrics.delivered.load(Ordering::SeqCst),
+            dropped: subscriber.metrics.dropped.load(Ordering::SeqCst),
+        })
+        .collect::<Vec<_>>();
+
+    stats.sort_by(|a, b| a.subscriber.cmp(&b.subscriber));
+
+    SubscriptionMetrics {
+        dropped: DROPPED_NOTIFICATIONS.load(Ordering::SeqCst),
+        subscribers: stats,
+    }
+}
+
+fn dispatch(notification: &str) {
+    let mut subscribers = SUBSCRIBERS.lock();
+
+    // Only pay the cost of parsing the notification if someone has asked for deltas
+    let value = if subscribers.values().any(|s| s.delta.is_some()) {
+        match serde_json::from_str::<serde_json::Value>(notification) {
+            Ok(value) => Option::from(value),
+            Err(error) => {
+                tracing::error!("could not parse notification for delta subscribers: {error}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let policy = *SUBSCRIPTION_OVERFLOW_POLICY.lock();
+    let mut disconnected = vec![];
+
+    for (key, subscriber) in subscribers.iter_mut() {
+        let payload = match (subscriber.delta.as_mut(), value.as_ref()) {
+            (Some(delta), Some(value)) => match serde_json::to_string(&delta.next(value)) {
+                Ok(payload) => payload,
+                Err(error) => {
+                    tracing::error!("could not serialize delta notification: {error}");
+                    continue;
+                }
+            },
+            _ => notification.to_string(),
+        };
+
+        if !subscriber.enqueue(key, payload, policy) {
+            disconnected.push(key.clone());
+        }
+    }
+
+    for key in disconnected {
+        tracing::warn!("removing subscription: {key}");
+        subscribers.remove(&key);
+    }
+}
+
+fn spawn_writer(
+    key: String,
+    id: u64,
+    mut transport: Transport,
+    receiver: Receiver<String>,
+    metrics: Arc<SubscriberMetrics>,
+) {
+    if let Transport::Stream(stream) = &transport {
+        if let Err(error) = stream.set_write_timeout(Option::from(WRITE_TIMEOUT)) {
+            tracing::warn!("could not set a write timeout for subscriber {key}: {error}");
+        }
+    }
+
+    let is_pipe = matches!(transport, Transport::Pipe(_));
+    let writing_since = Arc::new(Mutex::new(None));
+    let write_started = writing_since.clone();
+
+    let thread = std::thread::spawn(move || {
+        // This loop ends when the subscriber is removed and its sender is dropped
+        for payload in receiver.iter() {
+            *write_started.lock() = Option::from(Instant::now());
+            let delivered = transport.deliver(&payload);
+            *write_started.lock() = None;
+
+            match delivered {
+                Ok(()) => {
+                    metrics.delivered.fetch_add(1, Ordering::SeqCst);
+                    tracing::debug!("pushed notification to subscriber: {key}");
+                }
+                Err(error) => {
+                    if transport.is_stale(&error) {
+                        tracing::warn!("removing stale subscription: {key}");
+
+                        // The subscriber may have re-subscribed under the same name in the meantime
+                        let mut subscribers = SUBSCRIBERS.lock();
+                        if subscribers.get(&key).is_some_and(|s| s.id == id) {
+                            subscribers.remove(&key);
+                        }
+
+                        break;
+                    }
+
+                    tracing::warn!("could not push notification to subscriber {key}: {error}");
+                }
+            }
+        }
+    });
+
+    if is_pipe {
+        PIPE_WRITERS.lock().push(PipeWriter {
+            thread,
+            writing_since,
+        });
+    }
+}
@@ -0,0 +1,804 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use komorebi_core::Axis;
+use komorebi_core::Layout;
+use komorebi_core::Rect;
+
+use crate::container::Container;
+use crate::monitor::Monitor;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::windows_api::WindowsApi;
+use crate::workspace::Workspace;
+use crate::DATA_DIR;
+use crate::SCRATCHPAD_WINDOWS;
+use crate::WINDOW_PLACEMENT_ENABLED;
+use crate::WINDOW_PLACEMENT_INTERVAL;
+use crate::WINDOW_PLACEMENT_TOLERANCE;
+
+/// Identifies a window across restarts of komorebi and of the application itself
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindowFingerprint {
+    /// Handle of the window when it was recorded, which still identifies it if the window has
+    /// outlived komorebi
+    pub hwnd: isize,
+    pub exe: String,
+    pub class: String,
+    pub title: String,
+}
+
+impl WindowFingerprint {
+    pub fn of(window: Window) -> Option<Self> {
+        if let (Ok(exe), Ok(class), Ok(title)) = (window.exe(), window.class(), window.title()) {
+            return Option::from(Self {
+                hwnd: window.hwnd,
+                exe,
+                class,
+                title,
+            });
+        }
+
+        None
+    }
+
+    /// How well a window matches this fingerprint, if it matches at all; the same handle is the
+    /// best match, followed by the most similar title
+    fn score(&self, candidate: &Self, tolerance: f64) -> Option<f64> {
+        if self.exe != candidate.exe || self.class != candidate.class {
+            return None;
+        }
+
+        if self.hwnd == candidate.hwnd {
+            return Option::from(f64::INFINITY);
+        }
+
+        let similarity = title_similarity(&self.title, &candidate.title);
+        if 1.0 - similarity <= tolerance {
+            Option::from(similarity)
+        } else {
+            None
+        }
+    }
+}
+
+/// How alike two titles are, from 0.0 when every character differs to 1.0 when they are the
+/// same, based on the edit distance between them
+#[allow(clippy::cast_precision_loss)]
+fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// The candidate matched to each recorded window, by position; matches by handle are made before
+/// matches by title, and each candidate is matched at most once
+fn match_slots(
+    fingerprints: &[&WindowFingerprint],
+    candidates: &[&WindowFingerprint],
+    tolerance: f64,
+) -> Vec<Option<usize>> {
+    let mut matched = vec![None; fingerprints.len()];
+    let mut claimed = HashSet::new();
+
+    for exact in [true, false] {
+        for (slot, fingerprint) in fingerprints.iter().enumerate() {
+            if matched[slot].is_some() {
+                continue;
+            }
+
+            let best = candidates
+                .iter()
+                .enumerate()
+                .filter(|(idx, candidate)| {
+                    !claimed.contains(idx) && (!exact || candidate.hwnd == fingerprint.hwnd)
+                })
+                .filter_map(|(idx, candidate)| {
+                    Option::from((idx, fingerprint.score(candidate, tolerance)?))
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            if let Some((idx, _)) = best {
+                claimed.insert(idx);
+                matched[slot] = Option::from(idx);
+            }
+        }
+    }
+
+    matched
+}
+
+/// A stack of windows, from the bottom to the top
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContainerPlacement {
+    pub windows: Vec<WindowFingerprint>,
+    pub focused_window_idx: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FloatingPlacement {
+    pub window: WindowFingerprint,
+    /// Not recorded for windows which were minimized
+    pub rect: Option<Rect>,
+}
+
+/// Everything needed to arrange a workspace again
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspacePlacement {
+    pub workspace_idx: usize,
+    pub name: Option<String>,
+    pub layout: Layout,
+    pub layout_flip: Option<Axis>,
+    pub workspace_padding: Option<i32>,
+    pub container_padding: Option<i32>,
+    /// Tiled containers from the first to the last, including the monocle container
+    pub containers: Vec<ContainerPlacement>,
+    pub focused_container_idx: usize,
+    /// Position of the monocle container among the tiled containers, if there was one
+    pub monocle_container_idx: Option<usize>,
+    pub resize_dimensions: Vec<Option<Rect>>,
+    pub floating_windows: Vec<FloatingPlacement>,
+}
+
+impl WorkspacePlacement {
+    pub fn capture(workspace_idx: usize, workspace: &Workspace) -> Self {
+        let capture_container = |container: &Container| ContainerPlacement {
+            windows: container
+                .windows()
+                .iter()
+                .filter_map(|window| WindowFingerprint::of(*window))
+                .collect(),
+            focused_window_idx: container.focused_window_idx(),
+        };
+
+        let mut containers = workspace
+            .containers()
+            .iter()
+            .map(capture_container)
+            .collect::<Vec<_>>();
+
+        let mut monocle_container_idx = None;
+        if let Some(container) = workspace.monocle_container() {
+            let idx = workspace
+                .monocle_container_restore_idx()
+                .unwrap_or_default()
+                .min(containers.len());
+
+            containers.insert(idx, capture_container(container));
+            monocle_container_idx = Option::from(idx);
+        }
+
+        let floating_windows = workspace
+            .floating_windows()
+            .iter()
+            .filter_map(|window| {
+                Option::from(FloatingPlacement {
+                    window: WindowFingerprint::of(*window)?,
+                    rect: if window.is_miminized() {
+                        None
+                    } else {
+                        WindowsApi::window_rect(window.hwnd()).ok()
+                    },
+                })
+            })
+            .collect();
+
+        Self {
+            workspace_idx,
+            name: workspace.name().clone(),
+            layout: workspace.layout().clone(),
+            layout_flip: workspace.layout_flip(),
+            workspace_padding: workspace.workspace_padding(),
+            container_padding: workspace.container_padding(),
+            containers,
+            focused_container_idx: workspace.focused_container_idx(),
+            monocle_container_idx,
+            resize_dimensions: workspace.resize_dimensions().clone(),
+            floating_windows,
+        }
+    }
+
+    /// Find the workspace on a monitor by its name, or by its index if there is none by that
+    /// name
+    pub fn resolve(&self, monitor: &Monitor) -> usize {
+        self.name
+            .as_ref()
+            .and_then(|name| {
+                monitor
+                    .workspaces()
+                    .iter()
+                    .position(|workspace| workspace.name().as_ref() == Some(name))
+            })
+            .unwrap_or(self.workspace_idx)
+    }
+
+    fn fingerprints(&self) -> impl Iterator<Item = &WindowFingerprint> {
+        self.containers
+            .iter()
+            .flat_map(|container| container.windows.iter())
+            .chain(
+                self.floating_windows
+                    .iter()
+                    .map(|floating| &floating.window),
+            )
+    }
+
+    /// Arrange a workspace with the windows which have been matched to this placement, in the
+    /// same order as [`WorkspacePlacement::fingerprints`]; the containers which were already on
+    /// the workspace follow the restored ones
+    fn apply(
+        &self,
+        workspace: &mut Workspace,
+        windows: &mut impl Iterator<Item = Option<Window>>,
+    ) -> Result<()> {
+        if workspace.name().is_none() && self.name.is_some() {
+            workspace.set_name(self.name.clone());
+        }
+
+        workspace.set_layout(self.layout.clone());
+        workspace.set_layout_flip(self.layout_flip);
+        workspace.set_workspace_padding(self.workspace_padding);
+        workspace.set_container_padding(self.container_padding);
+
+        let mut restored = 0;
+        let mut monocle_idx = None;
+
+        for (container_idx, placement) in self.containers.iter().enumerate() {
+            let mut container = Container::default();
+            for _ in &placement.windows {
+                if let Some(Some(window)) = windows.next() {
+                    container.add_window(window);
+                }
+            }
+
+            if container.windows().is_empty() {
+                continue;
+            }
+
+            container.focus_window(
+                placement
+                    .focused_window_idx
+                    .min(container.windows().len() - 1),
+            );
+
+            if container.windows().len() > 1 {
+                container.load_focused_window()?;
+            }
+
+            if self.monocle_container_idx == Option::from(container_idx) {
+                monocle_idx = Option::from(restored);
+            }
+
+            workspace.insert_container_at_idx(restored, container);
+            restored += 1;
+        }
+
+        // Resize adjustments only make sense if all of the containers are where they were
+        let container_count = workspace.containers().len();
+        if restored == self.containers.len() && self.resize_dimensions.len() == container_count {
+            workspace.set_resize_dimensions(self.resize_dimensions.clone());
+        } else {
+            workspace.set_resize_dimensions(vec![None; container_count]);
+        }
+
+        if container_count > 0 {
+            workspace.focus_container(self.focused_container_idx.min(container_count - 1));
+        }
+
+        if let Some(idx) = monocle_idx {
+            if workspace.monocle_container().is_none() {
+                workspace.focus_container(idx);
+                workspace.new_monocle_container()?;
+            }
+        }
+
+        for placement in &self.floating_windows {
+            if let Some(Some(window)) = windows.next() {
+                if let Some(rect) = &placement.rect {
+                    window.set_position(rect, false)?;
+                }
+
+                workspace.floating_windows_mut().push(window);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MonitorPlacement {
+    /// Identifies the display, see [`Monitor::device_key`]
+    pub device_key: String,
+    pub monitor_idx: usize,
+    pub focused_workspace_idx: usize,
+    pub workspaces: Vec<WorkspacePlacement>,
+}
+
+impl MonitorPlacement {
+    pub fn capture(monitor_idx: usize, monitor: &Monitor) -> Self {
+        let tag_view_idx = monitor.tag_view().as_ref().map(|view| view.workspace_idx);
+
+        Self {
+            device_key: monitor.device_key(),
+            monitor_idx,
+            focused_workspace_idx: monitor.focused_workspace_idx(),
+            workspaces: monitor
+                .workspaces()
+                .iter()
+                .enumerate()
+                // The windows of a tag view are recorded where they were before it was shown
+                .filter(|(idx, _)| Option::from(*idx) != tag_view_idx)
+                .map(|(idx, workspace)| WorkspacePlacement::capture(idx, workspace))
+                .collect(),
+        }
+    }
+
+    /// Find the monitor by its display, or by its index if the display is not connected
+    pub fn resolve(&self, monitors: &VecDeque<Monitor>) -> Option<usize> {
+        monitors
+            .iter()
+            .position(|monitor| monitor.device_key() == self.device_key)
+            .or_else(|| (self.monitor_idx < monitors.len()).then_some(self.monitor_idx))
+    }
+}
+
+/// Where every managed window is, on every workspace of every monitor
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Placement {
+    pub focused_monitor_idx: usize,
+    pub monitors: Vec<MonitorPlacement>,
+    /// Windows in named scratchpads, which are hidden and not on any workspace
+    #[serde(default)]
+    pub scratchpads: HashMap<String, WindowFingerprint>,
+}
+
+impl From<&WindowManager> for Placement {
+    fn from(wm: &WindowManager) -> Self {
+        Self {
+            focused_monitor_idx: wm.focused_monitor_idx(),
+            monitors: wm
+                .monitors()
+                .iter()
+                .enumerate()
+                .map(|(idx, monitor)| MonitorPlacement::capture(idx, monitor))
+                .collect(),
+            scratchpads: SCRATCHPAD_WINDOWS
+                .lock()
+                .iter()
+                .filter_map(|(name, hwnd)| {
+                    WindowFingerprint::of(Window { hwnd: *hwnd })
+                        .map(|fingerprint| (name.clone(), fingerprint))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Placement {
+    pub fn window_count(&self) -> usize {
+        self.monitors
+            .iter()
+            .flat_map(|monitor| monitor.workspaces.iter())
+            .map(|workspace| workspace.fingerprints().count())
+            .sum()
+    }
+
+    /// The monitor and workspace indices where each recorded workspace should be arranged
+    pub fn targets(
+        &self,
+        monitors: &VecDeque<Monitor>,
+    ) -> Vec<(usize, usize, &WorkspacePlacement)> {
+        let mut targets = vec![];
+
+        for placement in &self.monitors {
+            let Some(monitor_idx) = placement.resolve(monitors) else {
+                tracing::warn!("skipping disconnected monitor {}", placement.device_key);
+                continue;
+            };
+
+            for workspace in &placement.workspaces {
+                targets.push((
+                    monitor_idx,
+                    workspace.resolve(&monitors[monitor_idx]),
+                    workspace,
+                ));
+            }
+        }
+
+        targets
+    }
+}
+
+/// Persist the placement of every managed window at an interval, so that it can be restored when
+/// komorebi restarts or recovers from a crash
+pub fn persist_periodically(wm: Arc<Mutex<WindowManager>>) {
+    std::thread::spawn(move || loop {
+        sleep(Duration::from_secs(
+            WINDOW_PLACEMENT_INTERVAL.load(Ordering::SeqCst).max(1),
+        ));
+
+        if !WINDOW_PLACEMENT_ENABLED.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        if let Err(error) = wm.lock().persist_placement() {
+            tracing::warn!("could not persist window placement: {error}");
+        }
+    });
+}
+
+impl WindowManager {
+    /// Every managed window on every workspace, with its fingerprint
+    fn managed_window_fingerprints(&self) -> Vec<(Window, WindowFingerprint)> {
+        let mut windows = vec![];
+
+        for monitor in self.monitors() {
+            for workspace in monitor.workspaces() {
+                let containers = workspace
+                    .containers()
+                    .iter()
+                    .chain(workspace.monocle_container());
+
+                for container in containers {
+                    windows.extend(container.windows().iter().copied());
+                }
+
+                windows.extend(workspace.floating_windows().iter().copied());
+            }
+        }
+
+        windows
+            .into_iter()
+            .filter_map(|window| Option::from((window, WindowFingerprint::of(window)?)))
+            .collect()
+    }
+
+    /// Move the matching managed windows into the recorded arrangements of workspaces, returning
+    /// how many windows were matched; exact matches by handle are made before matches by title
+    pub fn arrange_workspaces(
+        &mut self,
+        targets: &[(usize, usize, &WorkspacePlacement)],
+        tolerance: f64,
+    ) -> Result<usize> {
+        let candidates = self.managed_window_fingerprints();
+        let fingerprints = targets
+            .iter()
+            .flat_map(|(_, _, placement)| placement.fingerprints())
+            .collect::<Vec<_>>();
+
+        let matched = match_slots(
+            &fingerprints,
+            &candidates
+                .iter()
+                .map(|(_, candidate)| candidate)
+                .collect::<Vec<_>>(),
+            tolerance,
+        )
+        .into_iter()
+        .map(|slot| slot.map(|idx| candidates[idx].0))
+        .collect::<Vec<_>>();
+
+        for window in matched.iter().flatten() {
+            if let Some((monitor_idx, workspace_idx)) = self.window_location(window.hwnd) {
+                self.monitors_mut()[monitor_idx]
+                    .workspaces_mut()
+                    .get_mut(workspace_idx)
+                    .ok_or_else(|| anyhow!("there is no workspace"))?
+                    .remove_window(window.hwnd)?;
+            }
+        }
+
+        let restored = matched.iter().flatten().count();
+        let mut windows = matched.into_iter();
+
+        for (monitor_idx, workspace_idx, placement) in targets {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+            monitor.ensure_workspace_count(workspace_idx + 1);
+
+            let workspace = monitor
+                .workspaces_mut()
+                .get_mut(*workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+            placement.apply(workspace, &mut windows)?;
+        }
+
+        Ok(restored)
+    }
+
+    /// Focus the workspaces and the monitor which were focused when a placement was recorded
+    pub fn focus_placement(&mut self, placement: &Placement) -> Result<()> {
+        let mut focused_monitor_idx = None;
+
+        for (idx, monitor_placement) in placement.monitors.iter().enumerate() {
+            let Some(monitor_idx) = monitor_placement.resolve(self.monitors()) else {
+                continue;
+            };
+
+            let monitor = &mut self.monitors_mut()[monitor_idx];
+            if monitor_placement.focused_workspace_idx < monitor.workspaces().len() {
+                monitor.focus_workspace(monitor_placement.focused_workspace_idx)?;
+            }
+
+            if idx == placement.focused_monitor_idx {
+                focused_monitor_idx = Option::from(monitor_idx);
+            }
+        }
+
+        if let Some(idx) = focused_monitor_idx {
+            self.focus_monitor(idx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hide and restore the workspaces of every monitor once windows have been moved between
+    /// them, restoring the focused monitor last so that it keeps the foreground window
+    pub fn reload_workspaces(&mut self) -> Result<()> {
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let mut wait_for_minimization = false;
+        for monitor in self.monitors_mut() {
+            wait_for_minimization |= monitor.hide_unfocused_workspaces()?;
+        }
+
+        if wait_for_minimization {
+            sleep(Duration::from_millis(200));
+        }
+
+        let mut order = (0..self.monitors().len())
+            .filter(|idx| *idx != focused_monitor_idx)
+            .collect::<Vec<_>>();
+        order.push(focused_monitor_idx);
+
+        for idx in order {
+            if let Some(workspace) = self
+                .monitors()
+                .get(idx)
+                .and_then(|monitor| monitor.focused_workspace())
+            {
+                workspace.restore(mouse_follows_focus && idx == focused_monitor_idx)?;
+            }
+        }
+
+        self.retile_all(true)
+    }
+
+    pub fn persist_placement(&self) -> Result<()> {
+        let placement = Placement::from(self);
+
+        // Written in full before replacing the previous placement, which must survive a crash
+        let placement_json = DATA_DIR.join("komorebi.placement.json");
+        let pending_json = DATA_DIR.join("komorebi.placement.json.tmp");
+
+        std::fs::write(&pending_json, serde_json::to_string_pretty(&placement)?)?;
+        std::fs::rename(pending_json, placement_json)?;
+
+        Ok(())
+    }
+
+    /// Return the windows which are still open to where they were when the placement was last
+    /// persisted
+    #[tracing::instrument(skip(self))]
+    pub fn restore_placement(&mut self) -> Result<()> {
+        let placement_json = DATA_DIR.join("komorebi.placement.json");
+        if !placement_json.is_file() {
+            return Ok(());
+        }
+
+        tracing::info!("restoring window placement");
+
+        let placement: Placement = serde_json::from_reader(File::open(placement_json)?)?;
+
+        // Windows which were hidden on other workspaces when komorebi stopped are not picked up
+        // at startup, so they are taken back here as long as they are still the same windows
+        for fingerprint in placement
+            .monitors
+            .iter()
+            .flat_map(|monitor| monitor.workspaces.iter())
+            .flat_map(WorkspacePlacement::fingerprints)
+        {
+            let window = Window {
+                hwnd: fingerprint.hwnd,
+            };
+
+            if !window.is_window() || self.window_location(window.hwnd).is_some() {
+                continue;
+            }
+
+            if WindowFingerprint::of(window)
+                .is_some_and(|live| live.exe == fingerprint.exe && live.class == fingerprint.class)
+            {
+                let mut container = Container::default();
+                container.add_window(window);
+                self.focused_workspace_mut()?.add_container(container);
+            }
+        }
+
+        // Scratchpad windows are taken back into their scratchpads, so that they can be shown
+        // again even if komorebi was stopped without restoring them
+        for (name, fingerprint) in &placement.scratchpads {
+            let window = Window {
+                hwnd: fingerprint.hwnd,
+            };
+
+            if !window.is_window() {
+                continue;
+            }
+
+            if WindowFingerprint::of(window)
+                .is_some_and(|live| live.exe == fingerprint.exe && live.class == fingerprint.class)
+            {
+                SCRATCHPAD_WINDOWS.lock().insert(name.clone(), window.hwnd);
+            }
+        }
+
+        let tolerance = *WINDOW_PLACEMENT_TOLERANCE.lock();
+        let targets = placement.targets(self.monitors());
+        let restored = self.arrange_workspaces(&targets, tolerance)?;
+
+        self.focus_placement(&placement)?;
+        self.reload_workspaces()?;
+
+        tracing::info!("restored the placement of {restored} windows");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(hwnd: isize, title: &str) -> WindowFingerprint {
+        WindowFingerprint {
+            hwnd,
+            exe: String::from("editor.exe"),
+            class: String::from("EditorWindow"),
+            title: title.to_string(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn title_similarity_of_equal_and_empty_titles() {
+        assert_close(title_similarity("notes.txt", "notes.txt"), 1.0);
+        assert_close(title_similarity("", ""), 1.0);
+        assert_close(title_similarity("", "notes"), 0.0);
+    }
+
+    #[test]
+    fn title_similarity_is_based_on_edit_distance() {
+        assert_close(title_similarity("abc", "xyz"), 0.0);
+        assert_close(title_similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+        assert_close(title_similarity("sitting", "kitten"), 1.0 - 3.0 / 7.0);
+    }
+
+    #[test]
+    fn title_similarity_counts_characters() {
+        assert_close(title_similarity("café", "cafe"), 0.75);
+    }
+
+    #[test]
+    fn score_requires_the_same_application() {
+        let recorded = fingerprint(1, "notes.txt");
+
+        let mut other_exe = fingerprint(1, "notes.txt");
+        other_exe.exe = String::from("viewer.exe");
+        assert!(recorded.score(&other_exe, 1.0).is_none());
+
+        let mut other_class = fingerprint(1, "notes.txt");
+        other_class.class = String::from("ViewerWindow");
+        assert!(recorded.score(&other_class, 1.0).is_none());
+    }
+
+    #[test]
+    fn score_prefers_the_same_handle() {
+        let recorded = fingerprint(1, "notes.txt");
+
+        assert_eq!(
+            recorded.score(&fingerprint(1, "something else"), 0.0),
+            Option::from(f64::INFINITY)
+        );
+    }
+
+    #[test]
+    fn score_respects_the_tolerance() {
+        let recorded = fingerprint(1, "abcd");
+        let candidate = fingerprint(2, "abcx");
+
+        assert_eq!(recorded.score(&candidate, 0.25), Option::from(0.75));
+        assert_eq!(recorded.score(&candidate, 0.2), None);
+        assert_eq!(
+            recorded.score(&fingerprint(2, "abcd"), 0.0),
+            Option::from(1.0)
+        );
+    }
+
+    #[test]
+    fn match_slots_prefers_handles_over_titles() {
+        let recorded = fingerprint(1, "notes.txt");
+        let same_title = fingerprint(2, "notes.txt");
+        let same_handle = fingerprint(1, "notes.txt*");
+
+        assert_eq!(
+            match_slots(&[&recorded], &[&same_title, &same_handle], 0.5),
+            vec![Option::from(1)]
+        );
+    }
+
+    #[test]
+    fn match_slots_makes_exact_matches_first() {
+        let first = fingerprint(10, "notes.txt");
+        let second = fingerprint(20, "notes.txt");
+        let candidate = fingerprint(20, "notes.txt");
+
+        // The first slot would match by title, but the candidate is the window of the second slot
+        assert_eq!(
+            match_slots(&[&first, &second], &[&candidate], 0.5),
+            vec![None, Option::from(0)]
+        );
+    }
+
+    #[test]
+    fn match_slots_claims_each_candidate_once() {
+        let report = fingerprint(100, "report.txt");
+        let budget = fingerprint(101, "budget.txt");
+        let unrelated = fingerprint(102, "a completely different title");
+        let candidates = [
+            fingerprint(200, "budget.txt"),
+            fingerprint(201, "report.txt"),
+        ];
+
+        assert_eq!(
+            match_slots(
+                &[&report, &budget, &unrelated],
+                &candidates.iter().collect::<Vec<_>>(),
+                0.5
+            ),
+            vec![Option::from(1), Option::from(0), None]
+        );
+    }
+}
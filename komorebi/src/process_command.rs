@@ -4,6 +4,7 @@ use std::fs::OpenOptions;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Read;
+use std::net::IpAddr;
 use std::net::TcpListener;
 use std::net::TcpStream;
 use std::num::NonZeroUsize;
@@ -19,6 +20,7 @@ use net2::TcpStreamExt;
 use parking_lot::Mutex;
 use schemars::gen::SchemaSettings;
 use schemars::schema_for;
+use strum::Display;
 use uds_windows::UnixStream;
 
 use komorebi_core::config_generation::ApplicationConfiguration;
@@ -34,19 +36,29 @@ use komorebi_core::Rect;
 use komorebi_core::Sizing;
 use komorebi_core::SocketMessage;
 use komorebi_core::StateQuery;
+use komorebi_core::SubscriptionMode;
 use komorebi_core::WindowContainerBehaviour;
 use komorebi_core::WindowKind;
 
 use crate::border::Border;
 use crate::colour::Rgb;
+use crate::command_macros;
 use crate::current_virtual_desktop;
 use crate::gui_library::show_message;
+use crate::notification_dispatcher;
+use crate::notification_dispatcher::pipe_subscription_key;
+use crate::notification_dispatcher::socket_subscription_key;
+use crate::notification_dispatcher::stream_subscription_key;
+use crate::notification_dispatcher::websocket_subscription_key;
+use crate::notification_dispatcher::Transport;
 use crate::notify_subscribers;
+use crate::snapshots;
 use crate::static_config::StaticConfig;
 use crate::window::Window;
 use crate::window_manager;
 use crate::window_manager::WindowManager;
 use crate::windows_api::WindowsApi;
+use crate::workspace_groups;
 use crate::Notification;
 use crate::NotificationEvent;
 use crate::BORDER_COLOUR_CURRENT;
@@ -68,14 +80,20 @@ use crate::INITIAL_CONFIGURATION_LOADED;
 use crate::LAYERED_WHITELIST;
 use crate::MANAGE_IDENTIFIERS;
 use crate::MONITOR_INDEX_PREFERENCES;
+use crate::MONITOR_NAVIGATION_WRAP;
 use crate::NO_TITLEBAR;
 use crate::OBJECT_NAME_CHANGE_ON_LAUNCH;
 use crate::REMOVE_TITLEBARS;
-use crate::SUBSCRIPTION_PIPES;
-use crate::SUBSCRIPTION_SOCKETS;
+use crate::SCRATCHPAD_IDENTIFIERS;
+use crate::STICKY_IDENTIFIERS;
+use crate::SUBSCRIPTION_OVERFLOW_POLICY;
+use crate::SUBSCRIPTION_QUEUE_CAPACITY;
 use crate::TCP_CONNECTIONS;
+use crate::TCP_READ_ONLY_TOKENS;
+use crate::TCP_TOKENS;
 use crate::TRAY_AND_MULTI_WINDOW_IDENTIFIERS;
 use crate::UNMANAGE_IDENTIFIERS;
+use crate::WINDOW_PLACEMENT_ENABLED;
 use crate::WORKSPACE_RULES;
 
 #[tracing::instrument]
@@ -103,13 +121,78 @@ pub fn listen_for_commands(wm: Arc<Mutex<WindowManager>>) {
     });
 }
 
+/// The level of access granted to a client of a network listener
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ClientPermission {
+    /// All messages are allowed
+    Full,
+    /// Only queries and notification streams are allowed
+    ReadOnly,
+}
+
+impl ClientPermission {
+    #[must_use]
+    pub const fn allows(self, message: &SocketMessage) -> bool {
+        match self {
+            Self::Full => true,
+            Self::ReadOnly => {
+                message.is_query() || matches!(message, SocketMessage::AddSubscriberStream(..))
+            }
+        }
+    }
+}
+
+/// Clients must send `SocketMessage::Authenticate` before anything else if any tokens are set
+#[must_use]
+pub fn authentication_required() -> bool {
+    !TCP_TOKENS.lock().is_empty() || !TCP_READ_ONLY_TOKENS.lock().is_empty()
+}
+
+#[must_use]
+pub fn authenticate(token: &str) -> Option<ClientPermission> {
+    if TCP_TOKENS.lock().iter().any(|t| tokens_match(t, token)) {
+        return Option::from(ClientPermission::Full);
+    }
+
+    if TCP_READ_ONLY_TOKENS
+        .lock()
+        .iter()
+        .any(|t| tokens_match(t, token))
+    {
+        return Option::from(ClientPermission::ReadOnly);
+    }
+
+    None
+}
+
+// Compare every byte so that the time taken doesn't leak how much of a token was correct
+fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
 #[tracing::instrument]
-pub fn listen_for_commands_tcp(wm: Arc<Mutex<WindowManager>>, port: usize) {
-    let listener =
-        TcpListener::bind(format!("0.0.0.0:{port}")).expect("could not start tcp server");
+pub fn listen_for_commands_tcp(wm: Arc<Mutex<WindowManager>>, bind_address: IpAddr, port: usize) {
+    let address = match bind_address {
+        IpAddr::V4(ip) => format!("{ip}:{port}"),
+        IpAddr::V6(ip) => format!("[{ip}]:{port}"),
+    };
+
+    let listener = TcpListener::bind(&address).expect("could not start tcp server");
+
+    if !bind_address.is_loopback() && !authentication_required() {
+        tracing::warn!(
+            "the tcp server is listening on a non-loopback address without any authentication tokens configured"
+        );
+    }
 
     std::thread::spawn(move || {
-        tracing::info!("listening on 0.0.0.0:43663");
+        tracing::info!("listening on {address}");
         for client in listener.incoming() {
             match client {
                 Ok(mut stream) => {
@@ -129,12 +212,16 @@ pub fn listen_for_commands_tcp(wm: Arc<Mutex<WindowManager>>, port: usize) {
                         stream.try_clone().expect("stream should be cloneable"),
                     );
 
-                    tracing::info!("listening for incoming tcp messages from {}", &addr);
+                    drop(connections);
+
+                    tracing::info!("accepted tcp connection from {}", &addr);
 
                     match read_commands_tcp(&wm, &mut stream, &addr) {
                         Ok(()) => {}
                         Err(error) => tracing::error!("{}", error),
                     }
+
+                    tracing::info!("closed tcp connection from {}", &addr);
                 }
                 Err(error) => {
                     tracing::error!("{}", error);
@@ -167,11 +254,16 @@ impl WindowManager {
             }
         }
 
+        if message.is_journaled() && !self.journal.is_recording() {
+            return self.journaled(|wm| wm.process_command(message, reply));
+        }
+
         match message {
             SocketMessage::CycleFocusMonitor(_)
             | SocketMessage::CycleFocusWorkspace(_)
             | SocketMessage::FocusMonitorNumber(_)
             | SocketMessage::FocusMonitorWorkspaceNumber(_, _)
+            | SocketMessage::FocusWorkspaceGroup(_)
             | SocketMessage::FocusWorkspaceNumber(_) => {
                 if self.focused_workspace()?.visible_windows().is_empty() {
                     let border = Border::from(BORDER_HWND.load(Ordering::SeqCst));
@@ -225,9 +317,46 @@ impl WindowManager {
                 WindowsApi::center_cursor_in_rect(&focused_window_rect)?;
                 WindowsApi::left_click();
             }
+            SocketMessage::WithSelector(ref selector, ref message) => {
+                self.run_with_selector(selector, message)?;
+            }
+            SocketMessage::RunOrRaise(ref identifier, ref command, workspace) => {
+                self.run_or_raise(identifier, command.as_deref(), workspace)?;
+            }
+            SocketMessage::FocusWindowByHwnd(hwnd) => self.focus_window_by_hwnd(hwnd)?,
+            SocketMessage::FocusPreviousWindow => self.focus_previous_window()?,
+            SocketMessage::CycleFocusHistory(direction) => self.cycle_focus_history(direction)?,
+            SocketMessage::PopWorkspaceHistory => self.pop_workspace_history()?,
+            SocketMessage::MarkWindow(ref mark) => self.mark_focused_window(mark)?,
+            SocketMessage::FocusMark(ref mark) => self.focus_mark(mark)?,
+            SocketMessage::SwapWithMark(ref mark) => self.swap_with_mark(mark)?,
+            SocketMessage::TagWindow(ref tag) => self.tag_focused_window(tag)?,
+            SocketMessage::UntagWindow(ref tag) => self.untag_focused_window(tag)?,
+            SocketMessage::ViewTags(ref tags) => self.view_tags(tags.clone())?,
+            SocketMessage::SendToScratchpad(ref name) => self.send_to_scratchpad(name)?,
+            SocketMessage::ToggleScratchpad(ref name) => self.toggle_scratchpad(name)?,
+            SocketMessage::ScratchpadRule(ref name, identifier, ref id) => {
+                SCRATCHPAD_IDENTIFIERS.lock().insert(
+                    name.clone(),
+                    IdWithIdentifier {
+                        kind: identifier,
+                        id: id.clone(),
+                        matching_strategy: Option::from(MatchingStrategy::Legacy),
+                    },
+                );
+            }
+            SocketMessage::Marks => {
+                let marks = self.marks();
+                reply.write_all(serde_json::to_string_pretty(&marks)?.as_bytes())?;
+            }
+            SocketMessage::FocusHistory => {
+                let history = self.focus_history()?;
+                reply.write_all(serde_json::to_string_pretty(&history)?.as_bytes())?;
+            }
             SocketMessage::Close => self.focused_window()?.close()?,
             SocketMessage::Minimize => self.focused_window()?.minimize(),
             SocketMessage::ToggleFloat => self.toggle_float_on_focused_window()?,
+            SocketMessage::ToggleSticky(scope) => self.toggle_sticky(scope)?,
             SocketMessage::ToggleMonocle => self.toggle_monocle()?,
             SocketMessage::ToggleMaximize => self.toggle_maximize()?,
             SocketMessage::ContainerPadding(monitor_idx, workspace_idx, size) => {
@@ -310,6 +439,17 @@ impl WindowManager {
                     });
                 }
             }
+            SocketMessage::StickyRule(identifier, ref id) => {
+                let mut sticky_identifiers = STICKY_IDENTIFIERS.lock();
+
+                if !sticky_identifiers.iter().any(|s| s.id.eq(id)) {
+                    sticky_identifiers.push(IdWithIdentifier {
+                        kind: identifier,
+                        id: id.clone(),
+                        matching_strategy: Option::from(MatchingStrategy::Legacy),
+                    });
+                }
+            }
             SocketMessage::FloatRule(identifier, ref id) => {
                 let mut float_identifiers = FLOAT_IDENTIFIERS.lock();
 
@@ -556,7 +696,10 @@ impl WindowManager {
                 self.focus_monitor(monitor_idx)?;
                 self.update_focused_workspace(self.mouse_follows_focus)?;
             }
+            SocketMessage::ToggleVirtualMonitors => self.toggle_virtual_monitors()?,
             SocketMessage::Retile => self.retile_all(false)?,
+            SocketMessage::Undo => self.undo()?,
+            SocketMessage::Redo => self.redo()?,
             SocketMessage::FlipLayout(layout_flip) => self.flip_layout(layout_flip)?,
             SocketMessage::ChangeLayout(layout) => self.change_workspace_layout_default(layout)?,
             SocketMessage::CycleLayout(direction) => self.cycle_layout(direction)?,
@@ -773,16 +916,29 @@ impl WindowManager {
                 {
                     self.focus_monitor(monitor_idx)?;
                     self.focus_workspace(workspace_idx)?;
+                } else if workspace_groups::is_workspace_group(name) {
+                    self.focus_workspace_group(name)?;
                 }
 
                 if BORDER_ENABLED.load(Ordering::SeqCst) {
                     self.show_border()?;
                 };
             }
+            SocketMessage::FocusWorkspaceGroup(ref name) => {
+                show_message(&format!("{}", name))?;
+                self.focus_workspace_group(name)?;
+            }
             SocketMessage::Stop => {
                 tracing::info!(
                     "received stop command, restoring all hidden windows and terminating process"
                 );
+
+                if WINDOW_PLACEMENT_ENABLED.load(Ordering::SeqCst) {
+                    if let Err(error) = self.persist_placement() {
+                        tracing::warn!("could not persist window placement: {error}");
+                    }
+                }
+
                 self.restore_all_windows()?;
 
                 if WindowsApi::focus_follows_mouse()? {
@@ -813,6 +969,9 @@ impl WindowManager {
             SocketMessage::EnsureNamedWorkspaces(monitor_idx, ref names) => {
                 self.ensure_named_workspaces_for_monitor(monitor_idx, names)?;
             }
+            SocketMessage::DynamicWorkspaces(monitor_idx, enable) => {
+                self.set_dynamic_workspaces(monitor_idx, enable)?;
+            }
             SocketMessage::NewWorkspace => {
                 self.new_workspace()?;
             }
@@ -832,6 +991,10 @@ impl WindowManager {
 
                 tracing::info!("replying to state done");
             }
+            SocketMessage::SearchWindows(ref query, mode) => {
+                let results = self.search_windows(query.as_deref(), mode)?;
+                reply.write_all(serde_json::to_string_pretty(&results)?.as_bytes())?;
+            }
             SocketMessage::VisibleWindows => {
                 let mut monitor_visible_windows = HashMap::new();
 
@@ -1083,6 +1246,17 @@ impl WindowManager {
             SocketMessage::ReloadStaticConfiguration(ref pathbuf) => {
                 self.reload_static_configuration(pathbuf)?;
             }
+            SocketMessage::RunMacro(ref name, ref parameters) => {
+                for step in command_macros::expand(name, parameters)? {
+                    tracing::info!("running {step} from macro {name}");
+
+                    // Each step gets its own buffer so that this does not recurse into an
+                    // ever-deepening chain of &mut writers
+                    let mut output = vec![];
+                    self.process_command(step, &mut output)?;
+                    reply.write_all(&output)?;
+                }
+            }
             SocketMessage::CompleteConfiguration => {
                 if !INITIAL_CONFIGURATION_LOADED.load(Ordering::SeqCst) {
                     INITIAL_CONFIGURATION_LOADED.store(true, Ordering::SeqCst);
@@ -1219,6 +1393,13 @@ impl WindowManager {
 
                 serde_json::to_writer_pretty(&file, &resize)?;
             }
+            SocketMessage::SaveSnapshot(ref name, scope) => self.save_snapshot(name, scope)?,
+            SocketMessage::LoadSnapshot(ref name) => self.load_snapshot(name)?,
+            SocketMessage::DeleteSnapshot(ref name) => snapshots::delete(name)?,
+            SocketMessage::Snapshots => {
+                let summaries = snapshots::list()?;
+                reply.write_all(serde_json::to_string_pretty(&summaries)?.as_bytes())?;
+            }
             SocketMessage::Load(ref path) => {
                 let workspace = self.focused_workspace_mut()?;
 
@@ -1231,30 +1412,56 @@ impl WindowManager {
                 self.update_focused_workspace(false)?;
             }
             SocketMessage::AddSubscriberSocket(ref socket) => {
-                let mut sockets = SUBSCRIPTION_SOCKETS.lock();
-                let socket_path = DATA_DIR.join(socket);
-                sockets.insert(socket.clone(), socket_path);
+                add_subscriber_socket(socket, SubscriptionMode::Full);
+            }
+            SocketMessage::AddSubscriberSocketWithMode(ref socket, mode) => {
+                add_subscriber_socket(socket, mode);
+            }
+            SocketMessage::Authenticate(_) => {}
+            SocketMessage::AddSubscriberStream(..) => {
+                tracing::warn!("stream subscriptions are only supported by komorebi.sock and websocket clients");
             }
             SocketMessage::RemoveSubscriberSocket(ref socket) => {
-                let mut sockets = SUBSCRIPTION_SOCKETS.lock();
-                sockets.remove(socket);
+                notification_dispatcher::remove_subscriber(&socket_subscription_key(socket));
             }
             SocketMessage::AddSubscriberPipe(ref subscriber) => {
-                let mut pipes = SUBSCRIPTION_PIPES.lock();
-                let pipe_path = format!(r"\\.\pipe\{subscriber}");
-                let pipe = connect(&pipe_path).map_err(|_| {
-                    anyhow!("the named pipe '{}' has not yet been created; please create it before running this command", pipe_path)
-                })?;
-
-                pipes.insert(subscriber.clone(), pipe);
+                add_subscriber_pipe(subscriber, SubscriptionMode::Full)?;
+            }
+            SocketMessage::AddSubscriberPipeWithMode(ref subscriber, mode) => {
+                add_subscriber_pipe(subscriber, mode)?;
             }
             SocketMessage::RemoveSubscriberPipe(ref subscriber) => {
-                let mut pipes = SUBSCRIPTION_PIPES.lock();
-                pipes.remove(subscriber);
+                notification_dispatcher::remove_subscriber(&pipe_subscription_key(subscriber));
+            }
+            SocketMessage::ResyncSubscriber(ref subscriber) => {
+                notification_dispatcher::resync_subscriber(&socket_subscription_key(subscriber));
+                notification_dispatcher::resync_subscriber(&pipe_subscription_key(subscriber));
+                notification_dispatcher::resync_subscriber(&stream_subscription_key(subscriber));
+                notification_dispatcher::resync_subscriber(&websocket_subscription_key(subscriber));
+            }
+            SocketMessage::SubscriberOverflowPolicy(policy) => {
+                let mut overflow_policy = SUBSCRIPTION_OVERFLOW_POLICY.lock();
+                *overflow_policy = policy;
+            }
+            SocketMessage::SubscriberQueueCapacity(capacity) => {
+                SUBSCRIPTION_QUEUE_CAPACITY.store(capacity, Ordering::SeqCst);
+            }
+            SocketMessage::SubscriptionMetrics => {
+                let metrics = match serde_json::to_string_pretty(
+                    &notification_dispatcher::subscription_metrics(),
+                ) {
+                    Ok(metrics) => metrics,
+                    Err(error) => error.to_string(),
+                };
+
+                reply.write_all(metrics.as_bytes())?;
             }
             SocketMessage::MouseFollowsFocus(enable) => {
                 self.mouse_follows_focus = enable;
             }
+            SocketMessage::MonitorNavigationWrap(enable) => {
+                MONITOR_NAVIGATION_WRAP.store(enable, Ordering::SeqCst);
+            }
             SocketMessage::ToggleMouseFollowsFocus => {
                 self.mouse_follows_focus = !self.mouse_follows_focus;
             }
@@ -1380,6 +1587,9 @@ impl WindowManager {
             }
         };
 
+        self.update_tag_views()?;
+        self.update_dynamic_workspaces()?;
+
         match message {
             SocketMessage::ToggleMonocle => {
                 let current = BORDER_COLOUR_CURRENT.load(Ordering::SeqCst);
@@ -1427,6 +1637,7 @@ impl WindowManager {
             | SocketMessage::ResizeWindowEdge(_, _)
             | SocketMessage::ResizeWindowAxis(_, _)
             | SocketMessage::ToggleFloat
+            | SocketMessage::ToggleSticky(_)
             | SocketMessage::ToggleMonocle
             | SocketMessage::ToggleMaximize
             | SocketMessage::Promote
@@ -1434,6 +1645,9 @@ impl WindowManager {
             | SocketMessage::StackWindow(_)
             | SocketMessage::UnstackWindow
             | SocketMessage::Retile
+            | SocketMessage::ToggleVirtualMonitors
+            | SocketMessage::Undo
+            | SocketMessage::Redo
             // Adding this one so that changes can be seen instantly after
             // modifying the active window border offset
             | SocketMessage::ActiveWindowBorderOffset(_)
@@ -1441,6 +1655,14 @@ impl WindowManager {
             // getting sent on FocusWindow, meaning the border won't be set
             // when processing events
             | SocketMessage::FocusWindow(_)
+            | SocketMessage::FocusPreviousWindow
+            | SocketMessage::CycleFocusHistory(_)
+            | SocketMessage::FocusMark(_)
+            | SocketMessage::SwapWithMark(_)
+            | SocketMessage::ViewTags(_)
+            | SocketMessage::LoadSnapshot(_)
+            | SocketMessage::SendToScratchpad(_)
+            | SocketMessage::ToggleScratchpad(_)
             | SocketMessage::InvisibleBorders(_)
             | SocketMessage::WorkAreaOffset(_)
             | SocketMessage::CycleMoveWindow(_)
@@ -1449,6 +1671,8 @@ impl WindowManager {
             | SocketMessage::CycleFocusWorkspace(_)
             | SocketMessage::FocusMonitorNumber(_)
             | SocketMessage::FocusMonitorWorkspaceNumber(_, _)
+            | SocketMessage::FocusWorkspaceGroup(_)
+            | SocketMessage::PopWorkspaceHistory
             | SocketMessage::FocusWorkspaceNumber(_) => {
                 let foreground = WindowsApi::foreground_window()?;
                 let foreground_window = Window { hwnd: foreground };
@@ -1551,6 +1775,29 @@ impl WindowManager {
     }
 }
 
+fn add_subscriber_socket(socket: &str, mode: SubscriptionMode) {
+    notification_dispatcher::add_subscriber(
+        socket_subscription_key(socket),
+        Transport::Socket(DATA_DIR.join(socket)),
+        mode,
+    );
+}
+
+fn add_subscriber_pipe(subscriber: &str, mode: SubscriptionMode) -> Result<()> {
+    let pipe_path = format!(r"\\.\pipe\{subscriber}");
+    let pipe = connect(&pipe_path).map_err(|_| {
+        anyhow!("the named pipe '{}' has not yet been created; please create it before running this command", pipe_path)
+    })?;
+
+    notification_dispatcher::add_subscriber(
+        pipe_subscription_key(subscriber),
+        Transport::Pipe(pipe),
+        mode,
+    );
+
+    Ok(())
+}
+
 pub fn read_commands_uds(wm: &Arc<Mutex<WindowManager>>, mut stream: UnixStream) -> Result<()> {
     let reader = BufReader::new(stream.try_clone()?);
     // TODO(raggi): while this processes more than one command, if there are
@@ -1560,6 +1807,28 @@ pub fn read_commands_uds(wm: &Arc<Mutex<WindowManager>>, mut stream: UnixStream)
     for line in reader.lines() {
         let message = SocketMessage::from_str(&line?)?;
 
+        // The connection is handed over to the notification dispatcher and kept open
+        if let SocketMessage::AddSubscriberStream(ref subscriber, mode) = message {
+            notification_dispatcher::add_subscriber(
+                stream_subscription_key(subscriber),
+                Transport::Stream(stream),
+                mode,
+            );
+
+            let wm = wm.lock();
+            notify_subscribers(&serde_json::to_string(&Notification {
+                event: NotificationEvent::Socket(message.clone()),
+                state: wm.as_ref().into(),
+            })?)?;
+
+            return Ok(());
+        }
+
+        // Tokens are only meaningful for network listeners and should never be sent to subscribers
+        if let SocketMessage::Authenticate(_) = message {
+            continue;
+        }
+
         let mut wm = wm.lock();
 
         if wm.is_paused {
@@ -1590,11 +1859,16 @@ pub fn read_commands_tcp(
     addr: &str,
 ) -> Result<()> {
     let mut reader = BufReader::new(stream.try_clone()?);
+    let mut permission = if authentication_required() {
+        None
+    } else {
+        Option::from(ClientPermission::Full)
+    };
 
     loop {
         let mut buf = vec![0; 1024];
         match reader.read(&mut buf) {
-            Err(..) => {
+            Err(..) | Ok(0) => {
                 tracing::warn!("removing disconnected tcp client: {addr}");
                 let mut connections = TCP_CONNECTIONS.lock();
                 connections.remove(addr);
@@ -1609,6 +1883,39 @@ pub fn read_commands_tcp(
                     break;
                 };
 
+                if let SocketMessage::Authenticate(ref token) = message {
+                    if let Some(granted) = authenticate(token) {
+                        tracing::info!("tcp client authenticated with {granted} access: {addr}");
+                        permission = Option::from(granted);
+                        continue;
+                    }
+
+                    tracing::warn!("tcp client failed to authenticate, disconnecting: {addr}");
+                    let mut connections = TCP_CONNECTIONS.lock();
+                    connections.remove(addr);
+                    break;
+                }
+
+                match permission {
+                    None => {
+                        tracing::warn!(
+                            "tcp client sent a message before authenticating, disconnecting: {addr}"
+                        );
+                        let mut connections = TCP_CONNECTIONS.lock();
+                        connections.remove(addr);
+                        break;
+                    }
+                    Some(permission) if !permission.allows(&message) => {
+                        tracing::warn!(
+                            "ignoring {message} from tcp client with {permission} access: {addr}"
+                        );
+                        continue;
+                    }
+                    Some(_) => {
+                        tracing::info!("processing {message} from tcp client: {addr}");
+                    }
+                }
+
                 let mut wm = wm.lock();
 
                 if wm.is_paused {
@@ -16,7 +16,10 @@ use komorebi_core::WindowContainerBehaviour;
 
 use crate::border::Border;
 use crate::current_virtual_desktop;
+use crate::focus_history;
+use crate::forget_window;
 use crate::notify_subscribers;
+use crate::sticky;
 use crate::window::should_act;
 use crate::window_manager::WindowManager;
 use crate::window_manager_event::WindowManagerEvent;
@@ -90,6 +93,15 @@ impl WindowManager {
             }
         }
 
+        // Moving or resizing a tiled window with the mouse can be undone like the equivalent
+        // commands
+        if matches!(event, WindowManagerEvent::MoveResizeEnd(..))
+            && self.pending_move_op.is_some()
+            && !self.journal.is_recording()
+        {
+            return self.journaled(|wm| wm.process_event(event));
+        }
+
         // Make sure we have the most recently focused monitor from any event
         match event {
             WindowManagerEvent::FocusChange(_, window)
@@ -149,6 +161,49 @@ impl WindowManager {
             }
         }
 
+        let sticky_rule_scope = match event {
+            WindowManagerEvent::Show(_, window) | WindowManagerEvent::Manage(window)
+                if !sticky::is_sticky(window.hwnd)
+                    && self.window_location(window.hwnd).is_none() =>
+            {
+                sticky::sticky_rule_scope(*window)
+            }
+            _ => None,
+        };
+
+        if let Some(scope) = sticky_rule_scope {
+            let monitor_idx = self.focused_monitor_idx();
+            self.stick_window(event.window(), scope, monitor_idx)?;
+        }
+
+        // Sticky windows are kept by their monitor rather than by a workspace, so apart from
+        // keeping track of them, their events don't concern the layout
+        if sticky::is_sticky(event.window().hwnd) {
+            match event {
+                WindowManagerEvent::Destroy(_, window) | WindowManagerEvent::Unmanage(window) => {
+                    self.unstick_window(window.hwnd);
+                    forget_window(window.hwnd);
+                }
+                WindowManagerEvent::Hide(_, window) if !window.is_window() => {
+                    self.unstick_window(window.hwnd);
+                }
+                WindowManagerEvent::FocusChange(_, window) => {
+                    focus_history::record_focus(window.hwnd);
+                }
+                WindowManagerEvent::MoveResizeEnd(_, window) => {
+                    self.rehome_sticky_window(*window)?;
+                }
+                _ => {}
+            }
+
+            notify_subscribers(&serde_json::to_string(&Notification {
+                event: NotificationEvent::WindowManager(*event),
+                state: self.as_ref().into(),
+            })?)?;
+
+            return Ok(());
+        }
+
         match event {
             WindowManagerEvent::ForceUpdate(_) => {
                 self.update_focused_workspace(true)?;
@@ -159,6 +214,7 @@ impl WindowManager {
             }
             WindowManagerEvent::Destroy(_, window) | WindowManagerEvent::Unmanage(window) => {
                 self.remove_window(window)?;
+                forget_window(window.hwnd);
                 let mut already_moved_window_handles = self.already_moved_window_handles.lock();
                 already_moved_window_handles.remove(&window.hwnd);
             }
@@ -246,6 +302,8 @@ impl WindowManager {
                 already_moved_window_handles.remove(&window.hwnd);
             }
             WindowManagerEvent::FocusChange(_, window) => {
+                focus_history::record_focus(window.hwnd);
+
                 let workspace = self.focused_workspace_mut()?;
                 if !workspace
                     .floating_windows()
@@ -339,6 +397,7 @@ impl WindowManager {
                         }
                     }
                     self.enforce_workspace_rules()?;
+                    self.enforce_launch_workspace_rules(*window)?;
                 }
             }
             WindowManagerEvent::MoveResizeStart(_, window) => {
@@ -651,6 +710,9 @@ impl WindowManager {
             BORDER_HIDDEN.store(true, Ordering::SeqCst);
         }
 
+        self.update_tag_views()?;
+        self.update_dynamic_workspaces()?;
+
         tracing::trace!("updating list of known hwnds");
         let mut known_hwnds = vec![];
         for monitor in self.monitors() {
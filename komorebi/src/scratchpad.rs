@@ -0,0 +1,135 @@
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use komorebi_core::WindowSelector;
+
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::SCRATCHPAD_IDENTIFIERS;
+use crate::SCRATCHPAD_WINDOWS;
+
+/// Forget a scratchpad window which has been destroyed or unmanaged
+pub fn forget_window(hwnd: isize) {
+    SCRATCHPAD_WINDOWS
+        .lock()
+        .retain(|_, window| *window != hwnd);
+}
+
+impl WindowManager {
+    /// The window in a scratchpad, falling back to the first managed window which matches the
+    /// application identifier the scratchpad is bound to
+    fn scratchpad_window(&self, name: &str) -> Result<Option<isize>> {
+        let stored = SCRATCHPAD_WINDOWS.lock().get(name).copied();
+        if let Some(hwnd) = stored {
+            if (Window { hwnd }).is_window() {
+                return Ok(Option::from(hwnd));
+            }
+
+            crate::forget_window(hwnd);
+        }
+
+        let identifier = SCRATCHPAD_IDENTIFIERS.lock().get(name).cloned();
+        if let Some(identifier) = identifier {
+            if let Some(window) = self
+                .selected_windows(&WindowSelector::Application(identifier))?
+                .first()
+            {
+                SCRATCHPAD_WINDOWS
+                    .lock()
+                    .insert(name.to_string(), window.hwnd);
+
+                return Ok(Option::from(window.hwnd));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Stop managing a window wherever it is and hide it
+    fn hide_in_scratchpad(&mut self, hwnd: isize) -> Result<()> {
+        if let Some((monitor_idx, workspace_idx)) = self.window_location(hwnd) {
+            self.monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .remove_window(hwnd)?;
+        }
+
+        Window { hwnd }.hide();
+
+        Ok(())
+    }
+
+    /// Float a window centred on the focused workspace, taking it from wherever it was managed
+    fn show_from_scratchpad(&mut self, hwnd: isize) -> Result<()> {
+        if let Some((monitor_idx, workspace_idx)) = self.window_location(hwnd) {
+            self.monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .remove_window(hwnd)?;
+        }
+
+        let work_area = self.focused_monitor_work_area()?;
+        let mut window = Window { hwnd };
+
+        self.focused_workspace_mut()?
+            .floating_windows_mut()
+            .push(window);
+
+        window.restore()?;
+        window.center(&work_area)?;
+        self.update_focused_workspace(false)?;
+
+        window.focus(self.mouse_follows_focus)
+    }
+
+    /// Hide the focused window in a named scratchpad; a window which was already in that
+    /// scratchpad is shown again so that it doesn't stay hidden without a way to get it back
+    #[tracing::instrument(skip(self))]
+    pub fn send_to_scratchpad(&mut self, name: &str) -> Result<()> {
+        let hwnd = self.focused_window()?.hwnd;
+        let previous = SCRATCHPAD_WINDOWS.lock().insert(name.to_string(), hwnd);
+
+        self.hide_in_scratchpad(hwnd)?;
+
+        if let Some(previous) = previous {
+            if previous != hwnd
+                && (Window { hwnd: previous }).is_window()
+                && self.window_location(previous).is_none()
+            {
+                tracing::info!("replacing the window in scratchpad {name}");
+                return self.show_from_scratchpad(previous);
+            }
+        }
+
+        self.update_focused_workspace(self.mouse_follows_focus)
+    }
+
+    /// Show the window in a named scratchpad floating and centred on the focused workspace, or
+    /// hide it again if it is already shown there
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_scratchpad(&mut self, name: &str) -> Result<()> {
+        let Some(hwnd) = self.scratchpad_window(name)? else {
+            bail!("there is no window in the scratchpad {name}");
+        };
+
+        let shown_here = self
+            .focused_workspace()?
+            .floating_windows()
+            .iter()
+            .any(|window| window.hwnd == hwnd);
+
+        if shown_here {
+            self.hide_in_scratchpad(hwnd)?;
+            self.update_focused_workspace(self.mouse_follows_focus)
+        } else {
+            self.show_from_scratchpad(hwnd)
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::PathBuf;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use komorebi_core::SnapshotScope;
+
+use crate::placement::MonitorPlacement;
+use crate::placement::Placement;
+use crate::placement::WorkspacePlacement;
+use crate::window_manager::WindowManager;
+use crate::DATA_DIR;
+use crate::WINDOW_PLACEMENT_TOLERANCE;
+
+/// The arrangement of one workspace or of every monitor, saved under a name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Snapshot {
+    pub scope: SnapshotScope,
+    pub placement: Placement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub scope: SnapshotScope,
+    /// Number of windows in the arrangement
+    pub windows: usize,
+}
+
+fn snapshots_dir() -> PathBuf {
+    DATA_DIR.join("snapshots")
+}
+
+fn snapshot_path(name: &str) -> Result<PathBuf> {
+    let is_valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' ' | '.'))
+        && !name.starts_with('.');
+
+    if !is_valid {
+        bail!("{name} is not a valid snapshot name");
+    }
+
+    Ok(snapshots_dir().join(format!("{name}.json")))
+}
+
+fn read(name: &str) -> Result<Snapshot> {
+    let path = snapshot_path(name)?;
+    let file = File::open(&path).map_err(|_| anyhow!("there is no snapshot named {name}"))?;
+
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Every saved snapshot, ordered by name
+pub fn list() -> Result<Vec<SnapshotSummary>> {
+    let mut summaries = vec![];
+
+    let dir = snapshots_dir();
+    if !dir.is_dir() {
+        return Ok(summaries);
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            match read(name) {
+                Ok(snapshot) => summaries.push(SnapshotSummary {
+                    name: name.to_string(),
+                    scope: snapshot.scope,
+                    windows: snapshot.placement.window_count(),
+                }),
+                Err(error) => tracing::warn!("skipping snapshot {name}: {error}"),
+            }
+        }
+    }
+
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(summaries)
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    std::fs::remove_file(snapshot_path(name)?)
+        .map_err(|_| anyhow!("there is no snapshot named {name}"))?;
+
+    tracing::info!("deleted snapshot {name}");
+
+    Ok(())
+}
+
+impl WindowManager {
+    /// Save the arrangement of the focused workspace or of every monitor under a name, replacing
+    /// any snapshot which already has that name
+    #[tracing::instrument(skip(self))]
+    pub fn save_snapshot(&self, name: &str, scope: SnapshotScope) -> Result<()> {
+        let placement = match scope {
+            SnapshotScope::Workspace => {
+                let monitor_idx = self.focused_monitor_idx();
+                let monitor = self
+                    .focused_monitor()
+                    .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+                let workspace_idx = monitor.focused_workspace_idx();
+                let workspace = monitor
+                    .focused_workspace()
+                    .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+                Placement {
+                    focused_monitor_idx: monitor_idx,
+                    monitors: vec![MonitorPlacement {
+                        device_key: monitor.device_key(),
+                        monitor_idx,
+                        focused_workspace_idx: workspace_idx,
+                        workspaces: vec![WorkspacePlacement::capture(workspace_idx, workspace)],
+                    }],
+                    scratchpads: HashMap::new(),
+                }
+            }
+            SnapshotScope::AllMonitors => Placement::from(self),
+        };
+
+        let path = snapshot_path(name)?;
+        std::fs::create_dir_all(snapshots_dir())?;
+        std::fs::write(
+            path,
+            serde_json::to_string_pretty(&Snapshot { scope, placement })?,
+        )?;
+
+        tracing::info!("saved snapshot {name}");
+
+        Ok(())
+    }
+
+    /// Rearrange the open windows which match a snapshot into its arrangement; a snapshot of a
+    /// workspace is loaded onto the focused workspace
+    #[tracing::instrument(skip(self))]
+    pub fn load_snapshot(&mut self, name: &str) -> Result<()> {
+        let snapshot = read(name)?;
+        let tolerance = *WINDOW_PLACEMENT_TOLERANCE.lock();
+
+        let matched = match snapshot.scope {
+            SnapshotScope::Workspace => {
+                let mut workspace = snapshot
+                    .placement
+                    .monitors
+                    .first()
+                    .and_then(|monitor| monitor.workspaces.first())
+                    .cloned()
+                    .ok_or_else(|| anyhow!("snapshot {name} does not have a workspace"))?;
+
+                // The focused workspace keeps its own name
+                workspace.name = None;
+
+                let monitor_idx = self.focused_monitor_idx();
+                let workspace_idx = self.focused_workspace_idx_for_monitor_idx(monitor_idx)?;
+
+                self.arrange_workspaces(&[(monitor_idx, workspace_idx, &workspace)], tolerance)?
+            }
+            SnapshotScope::AllMonitors => {
+                let targets = snapshot.placement.targets(self.monitors());
+                let matched = self.arrange_workspaces(&targets, tolerance)?;
+
+                self.focus_placement(&snapshot.placement)?;
+
+                matched
+            }
+        };
+
+        self.reload_workspaces()?;
+
+        tracing::info!("loaded snapshot {name}, matching {matched} windows");
+
+        Ok(())
+    }
+}
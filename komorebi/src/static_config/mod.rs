@@ -5,6 +5,7 @@ pub mod popup;
 use crate::border::Border;
 use crate::colour::Colour;
 use crate::current_virtual_desktop;
+use crate::journal::Journal;
 use crate::monitor::Monitor;
 use crate::ring::Ring;
 use crate::window_manager::WindowManager;
@@ -22,20 +23,40 @@ use crate::BORDER_OVERFLOW_IDENTIFIERS;
 use crate::BORDER_WIDTH;
 use crate::DATA_DIR;
 use crate::DEFAULT_CONTAINER_PADDING;
+use crate::DEFAULT_MONITOR_CONFIGURATION;
 use crate::DEFAULT_WORKSPACE_PADDING;
 use crate::DISPLAY_INDEX_PREFERENCES;
 use crate::FINISH_MINIMIZE_ANIMATION;
 use crate::FLOAT_IDENTIFIERS;
 use crate::HIDING_BEHAVIOUR;
+use crate::HISTORY_DEPTH;
+use crate::LAUNCH_COMMANDS;
 use crate::LAYERED_WHITELIST;
+use crate::MACROS;
 use crate::MANAGE_IDENTIFIERS;
+use crate::MONITOR_ADJACENCY;
+use crate::MONITOR_CONFIGURATIONS;
 use crate::MONITOR_INDEX_PREFERENCES;
+use crate::MONITOR_NAVIGATION_WRAP;
 use crate::NATIVE_ANIMATION_DELAY;
 use crate::OBJECT_NAME_CHANGE_ON_LAUNCH;
 use crate::REGEX_IDENTIFIERS;
+use crate::SCRATCHPAD_IDENTIFIERS;
 use crate::STACK_BY_CATEGORY;
+use crate::STICKY_IDENTIFIERS;
+use crate::STICKY_RULE_SCOPE;
+use crate::SUBSCRIPTION_OVERFLOW_POLICY;
+use crate::SUBSCRIPTION_QUEUE_CAPACITY;
+use crate::TCP_BIND_ADDRESS;
+use crate::TCP_READ_ONLY_TOKENS;
+use crate::TCP_TOKENS;
 use crate::TRAY_AND_MULTI_WINDOW_IDENTIFIERS;
 use crate::UNMANAGE_IDENTIFIERS;
+use crate::WEBSOCKET_ALLOWED_ORIGINS;
+use crate::WINDOW_PLACEMENT_ENABLED;
+use crate::WINDOW_PLACEMENT_INTERVAL;
+use crate::WINDOW_PLACEMENT_TOLERANCE;
+use crate::WORKSPACE_GROUPS;
 use crate::WORKSPACE_RULES;
 use crate::top_bar::TopBarConfig;
 use crate::popup::PopupsConfig;
@@ -56,8 +77,11 @@ use komorebi_core::HidingBehaviour;
 use komorebi_core::Layout;
 use komorebi_core::MoveBehaviour;
 use komorebi_core::OperationBehaviour;
+use komorebi_core::OperationDirection;
+use komorebi_core::OverflowPolicy;
 use komorebi_core::Rect;
 use komorebi_core::SocketMessage;
+use komorebi_core::StickyScope;
 use komorebi_core::WindowContainerBehaviour;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
@@ -67,8 +91,10 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::io::ErrorKind;
 use std::io::Write;
+use std::net::IpAddr;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -90,7 +116,7 @@ pub struct ActiveWindowBorderColours {
     pub monocle: Colour,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WorkspaceConfig {
     /// Name
     pub name: String,
@@ -204,29 +230,173 @@ impl From<&Workspace> for WorkspaceConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MonitorConfig {
     /// Workspace configurations
     pub workspaces: Vec<WorkspaceConfig>,
     /// Monitor-specific work area offset (default: None)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub work_area_offset: Option<Rect>,
+    /// Ways of finding the display this configuration applies to, tried in order; without any,
+    /// the configuration applies to the monitor at the same index (default: None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matching: Option<Vec<MonitorMatcher>>,
+    /// Split the display into side-by-side virtual monitors, each with its own workspaces; when
+    /// set, the workspaces of the first virtual monitor are used instead of `workspaces`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub virtual_monitors: Option<Vec<VirtualMonitorConfig>>,
+    /// Remove empty workspaces without a name once they are left, and keep an empty workspace at
+    /// the end to open new windows on (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_workspaces: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VirtualMonitorConfig {
+    /// Width relative to the other virtual monitors on the display
+    pub width: u32,
+    /// Workspace configurations
+    pub workspaces: Vec<WorkspaceConfig>,
 }
 
 impl From<&Monitor> for MonitorConfig {
     fn from(value: &Monitor) -> Self {
         let mut workspaces = vec![];
-        for w in value.workspaces() {
+        for (i, w) in value.workspaces().iter().enumerate() {
+            // Workspaces without a name come and go on monitors with dynamic workspaces
+            if value.dynamic_workspaces() && w.name().is_none() {
+                continue;
+            }
+
+            // As does the workspace of a tag view
+            if value
+                .tag_view()
+                .as_ref()
+                .is_some_and(|view| view.workspace_idx == i)
+            {
+                continue;
+            }
+
             workspaces.push(WorkspaceConfig::from(w));
         }
 
         Self {
             workspaces,
             work_area_offset: value.work_area_offset(),
+            matching: value
+                .device_id()
+                .clone()
+                .map(|device_id| vec![MonitorMatcher::DeviceId(device_id)]),
+            virtual_monitors: None,
+            dynamic_workspaces: if value.dynamic_workspaces() {
+                Option::from(true)
+            } else {
+                None
+            },
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum MonitorMatcher {
+    /// Display name, for example DISPLAY1
+    Name(String),
+    /// Display device, for example DEL4310
+    Device(String),
+    /// Display device id, as shown in the window manager state
+    DeviceId(String),
+    /// Display resolution in pixels
+    Resolution { width: i32, height: i32 },
+    /// Display position relative to the other displays
+    Position(MonitorPosition),
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum MonitorPosition {
+    /// The display with its top left corner at the origin
+    Primary,
+    Leftmost,
+    Rightmost,
+    Topmost,
+    Bottommost,
+}
+
+impl MonitorMatcher {
+    pub fn matches(&self, monitor: &Monitor, monitors: &VecDeque<Monitor>) -> bool {
+        let size = monitor.size();
+
+        match self {
+            Self::Name(name) => monitor.name() == name,
+            Self::Device(device) => monitor.device().as_ref() == Some(device),
+            Self::DeviceId(device_id) => monitor.device_id().as_ref() == Some(device_id),
+            Self::Resolution { width, height } => size.right == *width && size.bottom == *height,
+            Self::Position(position) => {
+                let sizes = monitors.iter().map(Monitor::size);
+
+                match position {
+                    MonitorPosition::Primary => size.left == 0 && size.top == 0,
+                    MonitorPosition::Leftmost => sizes.map(|s| s.left).min() == Some(size.left),
+                    MonitorPosition::Rightmost => {
+                        sizes.map(|s| s.left + s.right).max() == Some(size.left + size.right)
+                    }
+                    MonitorPosition::Topmost => sizes.map(|s| s.top).min() == Some(size.top),
+                    MonitorPosition::Bottommost => {
+                        sizes.map(|s| s.top + s.bottom).max() == Some(size.top + size.bottom)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The monitor configuration which applies to each physical display, by monitor index; the
+/// later parts of a display split into virtual monitors are configured with the first part
+///
+/// Configurations with matchers claim displays first, in the order they are listed, and each
+/// takes the first display not already claimed which satisfies its earliest matcher that any
+/// display satisfies; configurations without matchers then apply to the display at their index if
+/// it is still unclaimed, and the default configuration applies to any display left over
+pub fn monitor_config_assignments<'a>(
+    configs: &'a [MonitorConfig],
+    default: Option<&'a MonitorConfig>,
+    monitors: &VecDeque<Monitor>,
+) -> Vec<Option<&'a MonitorConfig>> {
+    let mut assignments = vec![None; monitors.len()];
+    let physical = monitors
+        .iter()
+        .enumerate()
+        .filter(|(_, monitor)| monitor.is_physical())
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    for config in configs {
+        for matcher in config.matching.iter().flatten() {
+            let claimed = physical.iter().copied().find(|idx| {
+                assignments[*idx].is_none() && matcher.matches(&monitors[*idx], monitors)
+            });
+
+            if let Some(idx) = claimed {
+                assignments[idx] = Option::from(config);
+                break;
+            }
+        }
+    }
+
+    for (config, idx) in configs.iter().zip(&physical) {
+        if config.matching.is_none() && assignments[*idx].is_none() {
+            assignments[*idx] = Option::from(config);
+        }
+    }
+
+    for idx in physical {
+        if assignments[idx].is_none() {
+            assignments[idx] = default;
+        }
+    }
+
+    assignments
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AnimationsConfig {
     /// native windows animations delay for consecutive actions in ms (minimize, maximize, restore, etc)
@@ -235,6 +405,90 @@ pub struct AnimationsConfig {
     pub finish_miminization_before_restore: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MonitorNavigationConfig {
+    /// Wrap around to the monitor on the opposite side when there is none in a direction (default: false)
+    pub wrap: Option<bool>,
+    /// Explicit neighbours of monitors by device key, used instead of their positions; the device
+    /// key is the device id of a display, or its name if it has none, and the parts of a split
+    /// display add "#0", "#1", ... to it (default: None)
+    pub adjacency: Option<HashMap<String, MonitorNeighbours>>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MonitorNeighbours {
+    /// Device key of the monitor to the left
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<String>,
+    /// Device key of the monitor to the right
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<String>,
+    /// Device key of the monitor above
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up: Option<String>,
+    /// Device key of the monitor below
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down: Option<String>,
+}
+
+impl MonitorNeighbours {
+    pub const fn in_direction(&self, direction: OperationDirection) -> Option<&String> {
+        match direction {
+            OperationDirection::Left => self.left.as_ref(),
+            OperationDirection::Right => self.right.as_ref(),
+            OperationDirection::Up => self.up.as_ref(),
+            OperationDirection::Down => self.down.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WindowPlacementConfig {
+    /// Persist the placement of windows and restore it when komorebi starts (default: true)
+    pub enabled: Option<bool>,
+    /// Seconds between persisting the placement of windows (default: 30)
+    pub interval: Option<u64>,
+    /// Fraction of a window title which may have changed for the window to match its previous placement, from 0.0 to 1.0 (default: 0.5)
+    pub matching_tolerance: Option<f64>,
+}
+
+/// A workspace on a monitor, either by its index or by its name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum WorkspaceReference {
+    /// Workspace index (zero-indexed)
+    Index(usize),
+    /// Workspace name
+    Name(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubscriptionsConfig {
+    /// Maximum number of notifications queued for each subscriber added after this is set
+    /// (default: 64)
+    pub queue_capacity: Option<usize>,
+    /// What to do when a subscriber's notification queue is full (default: drop_oldest)
+    pub overflow_policy: Option<OverflowPolicy>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TcpConfig {
+    /// Address to bind the TCP command listener to when started with --tcp-port (default: 127.0.0.1)
+    pub bind_address: Option<IpAddr>,
+    /// Tokens granting full access; when any token is set, clients must send an Authenticate message first,
+    /// and WebSocket clients only get full access by sending one of these
+    pub tokens: Option<Vec<String>>,
+    /// Tokens granting access to queries only
+    pub read_only_tokens: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WebSocketConfig {
+    /// Origins of web pages allowed to connect to the WebSocket server started with --ws-port;
+    /// clients which do not send an Origin header are always allowed (default: none)
+    pub allowed_origins: Option<Vec<String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 /// The `komorebi.json` static configuration file reference for `v0.1.20`
 pub struct StaticConfig {
@@ -285,6 +539,9 @@ pub struct StaticConfig {
     /// Monitor and workspace configurations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub monitors: Option<Vec<MonitorConfig>>,
+    /// Monitor and workspace configuration for monitors which no other configuration applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_monitor: Option<MonitorConfig>,
     /// Which Windows signal to use when hiding windows (default: minimize)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub window_hiding_behaviour: Option<HidingBehaviour>,
@@ -297,6 +554,12 @@ pub struct StaticConfig {
     /// Individual window floating rules
     #[serde(skip_serializing_if = "Option::is_none")]
     pub float_rules: Option<Vec<IdWithIdentifier>>,
+    /// Individual window sticky rules, for windows which stay visible when switching workspaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticky_rules: Option<Vec<IdWithIdentifier>>,
+    /// Where windows made sticky by a rule stay visible (default: monitor)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticky_rule_scope: Option<StickyScope>,
     /// Individual excluded window floating rules
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_float_rules: Option<Vec<IdWithIdentifier>>,
@@ -333,6 +596,43 @@ pub struct StaticConfig {
     /// Set categories stackin behavior.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_stack_by_category: Option<bool>,
+    /// Named sequences of socket messages which can be run with 'komorebic run'; string
+    /// arguments of "$1", "$2", ... are replaced with the parameters given to the macro
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<HashMap<String, Vec<SocketMessage>>>")]
+    pub macros: Option<HashMap<String, Vec<serde_json::Value>>>,
+    /// Number of operations which can be undone (default: 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_depth: Option<usize>,
+    /// Named scratchpads bound to an application, so that toggling a scratchpad which is empty
+    /// picks up the first matching window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scratchpads: Option<HashMap<String, IdWithIdentifier>>,
+    /// Named commands which 'komorebic run-or-raise --launch' can run with 'cmd.exe /C' when no
+    /// managed window matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_commands: Option<HashMap<String, String>>,
+    /// Navigation between monitors by direction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_navigation: Option<MonitorNavigationConfig>,
+    /// Workspace groups which are focused together with a single command, each mapping the device
+    /// keys of monitors to the index or the name of a workspace on that monitor; the device key is
+    /// the device id of a display, or its name if it has none, and the parts of a split display
+    /// add "#0", "#1", ... to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_groups: Option<HashMap<String, HashMap<String, WorkspaceReference>>>,
+    /// Persistence of the placement of windows across restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_placement: Option<WindowPlacementConfig>,
+    /// Event notification subscription configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscriptions: Option<SubscriptionsConfig>,
+    /// TCP command listener configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<TcpConfig>,
+    /// WebSocket server configuration; authentication uses the tokens from the tcp configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<WebSocketConfig>,
 }
 
 impl From<&WindowManager> for StaticConfig {
@@ -388,6 +688,34 @@ impl From<&WindowManager> for StaticConfig {
             }
         }
 
+        // Fold the virtual monitors of a split display back into the configuration of the display
+        let mut split_monitors: Vec<MonitorConfig> = vec![];
+        for (mut config, monitor) in monitors.into_iter().zip(value.monitors()) {
+            match monitor.virtual_split() {
+                None => split_monitors.push(config),
+                Some(split) => {
+                    let width = split.widths.get(split.part).copied().unwrap_or(1);
+                    let part = VirtualMonitorConfig {
+                        width,
+                        workspaces: std::mem::take(&mut config.workspaces),
+                    };
+
+                    match split_monitors.last_mut() {
+                        Some(physical) if split.part > 0 => physical
+                            .virtual_monitors
+                            .get_or_insert_with(Vec::new)
+                            .push(part),
+                        _ => {
+                            config.virtual_monitors = Option::from(vec![part]);
+                            split_monitors.push(config);
+                        }
+                    }
+                }
+            }
+        }
+
+        let monitors = split_monitors;
+
         let border_colours = if BORDER_COLOUR_SINGLE.load(Ordering::SeqCst) == 0 {
             None
         } else {
@@ -428,6 +756,7 @@ impl From<&WindowManager> for StaticConfig {
                 DEFAULT_CONTAINER_PADDING.load(Ordering::SeqCst),
             ),
             monitors: Option::from(monitors),
+            default_monitor: DEFAULT_MONITOR_CONFIGURATION.lock().clone(),
             window_hiding_behaviour: Option::from(*HIDING_BEHAVIOUR.lock()),
             global_work_area_offset: value.work_area_offset,
             animations: Option::from(AnimationsConfig {
@@ -439,6 +768,8 @@ impl From<&WindowManager> for StaticConfig {
                 ),
             }),
             float_rules: None,
+            sticky_rules: None,
+            sticky_rule_scope: Option::from(*STICKY_RULE_SCOPE.lock()),
             exclude_float_rules: None,
             manage_rules: None,
             unmanage_rules: None,
@@ -451,6 +782,28 @@ impl From<&WindowManager> for StaticConfig {
             top_bar: Option::from(TopBarConfig::clone_globals()),
             popups: Option::from(PopupsConfig::clone_globals()),
             auto_stack_by_category: None,
+            macros: Option::from(MACROS.lock().clone()),
+            history_depth: Option::from(HISTORY_DEPTH.load(Ordering::SeqCst)),
+            scratchpads: Option::from(SCRATCHPAD_IDENTIFIERS.lock().clone()),
+            launch_commands: Option::from(LAUNCH_COMMANDS.lock().clone()),
+            monitor_navigation: Option::from(MonitorNavigationConfig {
+                wrap: Option::from(MONITOR_NAVIGATION_WRAP.load(Ordering::SeqCst)),
+                adjacency: Option::from(MONITOR_ADJACENCY.lock().clone()),
+            }),
+            workspace_groups: Option::from(WORKSPACE_GROUPS.lock().clone()),
+            window_placement: Option::from(WindowPlacementConfig {
+                enabled: Option::from(WINDOW_PLACEMENT_ENABLED.load(Ordering::SeqCst)),
+                interval: Option::from(WINDOW_PLACEMENT_INTERVAL.load(Ordering::SeqCst)),
+                matching_tolerance: Option::from(*WINDOW_PLACEMENT_TOLERANCE.lock()),
+            }),
+            subscriptions: Option::from(SubscriptionsConfig {
+                queue_capacity: Option::from(SUBSCRIPTION_QUEUE_CAPACITY.load(Ordering::SeqCst)),
+                overflow_policy: Option::from(*SUBSCRIPTION_OVERFLOW_POLICY.lock()),
+            }),
+            tcp: None,
+            websocket: Option::from(WebSocketConfig {
+                allowed_origins: Option::from(WEBSOCKET_ALLOWED_ORIGINS.lock().clone()),
+            }),
         }
     }
 }
@@ -511,6 +864,122 @@ impl StaticConfig {
             *window_hiding_behaviour = behaviour;
         }
 
+        {
+            let mut monitor_configurations = MONITOR_CONFIGURATIONS.lock();
+            *monitor_configurations = self.monitors.clone().unwrap_or_default();
+
+            let mut default_monitor_configuration = DEFAULT_MONITOR_CONFIGURATION.lock();
+            *default_monitor_configuration = self.default_monitor.clone();
+        }
+
+        // Macros are always replaced so that removing them from the config stops them being run
+        {
+            let mut macros = MACROS.lock();
+            *macros = self.macros.clone().unwrap_or_default();
+        }
+
+        if let Some(depth) = self.history_depth {
+            HISTORY_DEPTH.store(depth, Ordering::SeqCst);
+        }
+
+        // Scratchpad bindings are always replaced so that removing them from the config forgets them
+        {
+            let mut scratchpad_identifiers = SCRATCHPAD_IDENTIFIERS.lock();
+            *scratchpad_identifiers = self.scratchpads.clone().unwrap_or_default();
+        }
+
+        // Commands are always replaced so that removing them from the config stops them being run
+        {
+            let mut launch_commands = LAUNCH_COMMANDS.lock();
+            *launch_commands = self.launch_commands.clone().unwrap_or_default();
+        }
+
+        if let Some(scope) = self.sticky_rule_scope {
+            let mut sticky_rule_scope = STICKY_RULE_SCOPE.lock();
+            *sticky_rule_scope = scope;
+        }
+
+        if let Some(wrap) = self
+            .monitor_navigation
+            .as_ref()
+            .and_then(|navigation| navigation.wrap)
+        {
+            MONITOR_NAVIGATION_WRAP.store(wrap, Ordering::SeqCst);
+        }
+
+        // The adjacency is always replaced so that neighbours removed from the config are forgotten
+        {
+            let mut adjacency = MONITOR_ADJACENCY.lock();
+            *adjacency = self
+                .monitor_navigation
+                .as_ref()
+                .and_then(|navigation| navigation.adjacency.clone())
+                .unwrap_or_default();
+        }
+
+        // Groups are always replaced so that removing them from the config forgets them
+        {
+            let mut workspace_groups = WORKSPACE_GROUPS.lock();
+            *workspace_groups = self.workspace_groups.clone().unwrap_or_default();
+        }
+
+        if let Some(placement) = &self.window_placement {
+            if let Some(enabled) = placement.enabled {
+                WINDOW_PLACEMENT_ENABLED.store(enabled, Ordering::SeqCst);
+            }
+
+            if let Some(interval) = placement.interval {
+                WINDOW_PLACEMENT_INTERVAL.store(interval, Ordering::SeqCst);
+            }
+
+            if let Some(tolerance) = placement.matching_tolerance {
+                let mut matching_tolerance = WINDOW_PLACEMENT_TOLERANCE.lock();
+                *matching_tolerance = tolerance.clamp(0.0, 1.0);
+            }
+        }
+
+        if let Some(subscriptions) = &self.subscriptions {
+            if let Some(capacity) = subscriptions.queue_capacity {
+                SUBSCRIPTION_QUEUE_CAPACITY.store(capacity, Ordering::SeqCst);
+            }
+
+            if let Some(policy) = subscriptions.overflow_policy {
+                let mut overflow_policy = SUBSCRIPTION_OVERFLOW_POLICY.lock();
+                *overflow_policy = policy;
+            }
+        }
+
+        if let Some(bind_address) = self.tcp.as_ref().and_then(|tcp| tcp.bind_address) {
+            let mut address = TCP_BIND_ADDRESS.lock();
+            *address = bind_address;
+        }
+
+        // Tokens are always replaced so that removing them from the config revokes them
+        {
+            let mut tokens = TCP_TOKENS.lock();
+            *tokens = self
+                .tcp
+                .as_ref()
+                .and_then(|tcp| tcp.tokens.clone())
+                .unwrap_or_default();
+
+            let mut read_only_tokens = TCP_READ_ONLY_TOKENS.lock();
+            *read_only_tokens = self
+                .tcp
+                .as_ref()
+                .and_then(|tcp| tcp.read_only_tokens.clone())
+                .unwrap_or_default();
+        }
+
+        {
+            let mut allowed_origins = WEBSOCKET_ALLOWED_ORIGINS.lock();
+            *allowed_origins = self
+                .websocket
+                .as_ref()
+                .and_then(|websocket| websocket.allowed_origins.clone())
+                .unwrap_or_default();
+        }
+
         if let Some(container) = self.default_container_padding {
             DEFAULT_CONTAINER_PADDING.store(container, Ordering::SeqCst);
         }
@@ -558,12 +1027,18 @@ impl StaticConfig {
         let mut border_overflow_identifiers = BORDER_OVERFLOW_IDENTIFIERS.lock();
         let mut object_name_change_identifiers = OBJECT_NAME_CHANGE_ON_LAUNCH.lock();
         let mut layered_identifiers = LAYERED_WHITELIST.lock();
+        let mut sticky_identifiers = STICKY_IDENTIFIERS.lock();
 
         Self::apply_global(
             &mut self.float_rules,
             &mut float_identifiers,
             &mut regex_identifiers,
         )?;
+        Self::apply_global(
+            &mut self.sticky_rules,
+            &mut sticky_identifiers,
+            &mut regex_identifiers,
+        )?;
         Self::apply_global(
             &mut self.manage_rules,
             &mut manage_identifiers,
@@ -815,6 +1290,7 @@ impl StaticConfig {
             has_pending_raise_op: false,
             pending_move_op: None,
             already_moved_window_handles: Arc::new(Mutex::new(HashSet::new())),
+            journal: Journal::default(),
         };
 
         match value.focus_follows_mouse {
@@ -849,23 +1325,7 @@ impl StaticConfig {
         let value: Self = serde_json::from_str(&content)?;
         let mut wm = wm.lock();
 
-        if let Some(monitors) = value.monitors {
-            for (i, monitor) in monitors.iter().enumerate() {
-                if let Some(m) = wm.monitors_mut().get_mut(i) {
-                    m.ensure_workspace_count(monitor.workspaces.len());
-                    m.set_work_area_offset(monitor.work_area_offset);
-
-                    for (j, ws) in m.workspaces_mut().iter_mut().enumerate() {
-                        ws.load_static_config(
-                            monitor
-                                .workspaces
-                                .get(j)
-                                .expect("no static workspace config"),
-                        )?;
-                    }
-                }
-            }
-        }
+        wm.apply_monitor_configurations(None)?;
 
         let _ = wm.enforce_workspace_rules();
 
@@ -887,23 +1347,7 @@ impl StaticConfig {
 
         value.apply_globals()?;
 
-        if let Some(monitors) = value.monitors {
-            for (i, monitor) in monitors.iter().enumerate() {
-                if let Some(m) = wm.monitors_mut().get_mut(i) {
-                    m.ensure_workspace_count(monitor.workspaces.len());
-                    m.set_work_area_offset(monitor.work_area_offset);
-
-                    for (j, ws) in m.workspaces_mut().iter_mut().enumerate() {
-                        ws.load_static_config(
-                            monitor
-                                .workspaces
-                                .get(j)
-                                .expect("no static workspace config"),
-                        )?;
-                    }
-                }
-            }
-        }
+        wm.apply_monitor_configurations(None)?;
 
         if value.active_window_border == Some(true) {
             if BORDER_HWND.load(Ordering::SeqCst) == 0 {
@@ -958,3 +1402,203 @@ impl StaticConfig {
         Ok(())
     }
 }
+
+impl WindowManager {
+    /// Apply the monitor configurations from the static configuration to the monitors they
+    /// match, or only to the monitor at the given index
+    pub fn apply_monitor_configurations(&mut self, monitor_idx: Option<usize>) -> Result<()> {
+        let configs = MONITOR_CONFIGURATIONS.lock().clone();
+        let default = DEFAULT_MONITOR_CONFIGURATION.lock().clone();
+        let assignments = monitor_config_assignments(&configs, default.as_ref(), self.monitors());
+
+        // In reverse, so that splitting a display doesn't move the monitors still to be configured
+        for (i, config) in assignments.into_iter().enumerate().rev() {
+            if monitor_idx.is_some_and(|idx| idx != i) {
+                continue;
+            }
+
+            let Some(config) = config else {
+                continue;
+            };
+
+            match &config.virtual_monitors {
+                Some(parts) if parts.len() > 1 => {
+                    self.split_monitor(i, parts.iter().map(|part| part.width).collect())?;
+
+                    for (part_idx, part) in parts.iter().enumerate() {
+                        if let Some(monitor) = self.monitors_mut().get_mut(i + part_idx) {
+                            monitor.set_work_area_offset(config.work_area_offset);
+                            monitor.set_dynamic_workspaces(
+                                config.dynamic_workspaces.unwrap_or_default(),
+                            );
+                            monitor.load_workspace_configs(&part.workspaces)?;
+                        }
+                    }
+                }
+                _ => {
+                    if self
+                        .monitors()
+                        .get(i)
+                        .is_some_and(|monitor| monitor.virtual_split().is_some())
+                    {
+                        self.merge_monitor(i)?;
+                    }
+
+                    if let Some(monitor) = self.monitors_mut().get_mut(i) {
+                        monitor.load_static_config(config)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor;
+    use crate::monitor::VirtualSplit;
+
+    fn display(name: &str, left: i32, width: i32) -> Monitor {
+        let size = Rect {
+            left,
+            top: 0,
+            right: width,
+            bottom: 1080,
+        };
+
+        let mut monitor = monitor::new(0, size, size, name.to_string());
+        monitor.set_device_id(Option::from(format!("{name}-id")));
+        monitor
+    }
+
+    fn config(matching: Option<Vec<MonitorMatcher>>) -> MonitorConfig {
+        MonitorConfig {
+            workspaces: vec![],
+            work_area_offset: None,
+            matching,
+            virtual_monitors: None,
+            dynamic_workspaces: None,
+        }
+    }
+
+    /// The position of the configuration assigned to each monitor, where the default
+    /// configuration is the last one
+    fn assigned(configs: &[MonitorConfig], monitors: &VecDeque<Monitor>) -> Vec<Option<usize>> {
+        let (default, listed) = configs.split_last().unwrap();
+
+        monitor_config_assignments(listed, Option::from(default), monitors)
+            .iter()
+            .map(|assignment| {
+                assignment.and_then(|config| configs.iter().position(|c| std::ptr::eq(c, config)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn configs_without_matchers_apply_by_index() {
+        let monitors = VecDeque::from(vec![
+            display("DISPLAY1", 0, 1920),
+            display("DISPLAY2", 1920, 1920),
+            display("DISPLAY3", 3840, 1920),
+        ]);
+        let configs = vec![config(None), config(None), config(None)];
+
+        assert_eq!(
+            assigned(&configs, &monitors),
+            vec![Option::from(0), Option::from(1), Option::from(2)]
+        );
+    }
+
+    #[test]
+    fn matchers_claim_displays_before_indices() {
+        let monitors = VecDeque::from(vec![
+            display("DISPLAY1", 0, 1920),
+            display("DISPLAY2", 1920, 2560),
+        ]);
+        let configs = vec![
+            config(None),
+            config(Option::from(vec![MonitorMatcher::Name(String::from(
+                "DISPLAY1",
+            ))])),
+            config(None),
+        ];
+
+        // The first configuration would apply to the first display by its index, but that
+        // display has been claimed, so it falls to the default
+        assert_eq!(
+            assigned(&configs, &monitors),
+            vec![Option::from(1), Option::from(2)]
+        );
+    }
+
+    #[test]
+    fn matchers_are_tried_in_order() {
+        let monitors = VecDeque::from(vec![
+            display("DISPLAY1", 0, 1920),
+            display("DISPLAY2", 1920, 2560),
+        ]);
+        let configs = vec![
+            config(Option::from(vec![
+                MonitorMatcher::DeviceId(String::from("disconnected")),
+                MonitorMatcher::Resolution {
+                    width: 2560,
+                    height: 1080,
+                },
+                MonitorMatcher::Position(MonitorPosition::Primary),
+            ])),
+            config(None),
+        ];
+
+        assert_eq!(
+            assigned(&configs, &monitors),
+            vec![Option::from(1), Option::from(0)]
+        );
+    }
+
+    #[test]
+    fn each_display_is_claimed_once() {
+        let monitors = VecDeque::from(vec![
+            display("DISPLAY1", 0, 1920),
+            display("DISPLAY2", 1920, 1920),
+        ]);
+        let leftmost = || {
+            config(Option::from(vec![
+                MonitorMatcher::Position(MonitorPosition::Leftmost),
+                MonitorMatcher::Position(MonitorPosition::Rightmost),
+            ]))
+        };
+        let configs = vec![leftmost(), leftmost(), leftmost(), config(None)];
+
+        assert_eq!(
+            assigned(&configs, &monitors),
+            vec![Option::from(0), Option::from(1)]
+        );
+    }
+
+    #[test]
+    fn virtual_monitors_are_not_assigned() {
+        let mut first = display("DISPLAY1", 0, 3840);
+        let mut second = display("DISPLAY1", 0, 3840);
+        first.set_virtual_split(Option::from(VirtualSplit {
+            part: 0,
+            widths: vec![1, 1],
+        }));
+        second.set_virtual_split(Option::from(VirtualSplit {
+            part: 1,
+            widths: vec![1, 1],
+        }));
+
+        let monitors = VecDeque::from(vec![first, second, display("DISPLAY2", 3840, 1920)]);
+        let configs = vec![config(None), config(None), config(None)];
+
+        // Configurations by index count physical displays, so the second one applies to the
+        // display after the split one
+        assert_eq!(
+            assigned(&configs, &monitors),
+            vec![Option::from(0), None, Option::from(1)]
+        );
+    }
+}
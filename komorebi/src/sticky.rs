@@ -0,0 +1,225 @@
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use komorebi_core::Rect;
+use komorebi_core::StickyScope;
+
+use crate::window::should_act;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::windows_api::WindowsApi;
+use crate::REGEX_IDENTIFIERS;
+use crate::STICKY_HWNDS;
+use crate::STICKY_IDENTIFIERS;
+use crate::STICKY_RULE_SCOPE;
+
+/// A window which stays visible when switching workspaces, kept by the monitor it is on rather
+/// than by any of its workspaces
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct StickyWindow {
+    pub window: Window,
+    pub scope: StickyScope,
+}
+
+pub fn is_sticky(hwnd: isize) -> bool {
+    STICKY_HWNDS.lock().contains(&hwnd)
+}
+
+/// The scope a window should be made sticky with if it matches a sticky rule
+pub fn sticky_rule_scope(window: Window) -> Option<StickyScope> {
+    if let (Ok(title), Ok(exe_name), Ok(class), Ok(path)) =
+        (window.title(), window.exe(), window.class(), window.path())
+    {
+        if should_act(
+            &title,
+            &exe_name,
+            &class,
+            &path,
+            &STICKY_IDENTIFIERS.lock(),
+            &REGEX_IDENTIFIERS.lock(),
+        ) {
+            return Option::from(*STICKY_RULE_SCOPE.lock());
+        }
+    }
+
+    None
+}
+
+/// Move a rectangle from one work area to another, keeping its offset from the top left corner
+/// as far as it still fits
+fn relocate(rect: &Rect, from: &Rect, to: &Rect) -> Rect {
+    let left = (rect.left - from.left).clamp(0, (to.right - rect.right).max(0));
+    let top = (rect.top - from.top).clamp(0, (to.bottom - rect.bottom).max(0));
+
+    Rect {
+        left: to.left + left,
+        top: to.top + top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
+
+impl WindowManager {
+    /// The monitor index and the position on that monitor of a sticky window
+    fn sticky_window_position(&self, hwnd: isize) -> Option<(usize, usize)> {
+        for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
+            if let Some(idx) = monitor
+                .sticky_windows()
+                .iter()
+                .position(|sticky| sticky.window.hwnd == hwnd)
+            {
+                return Option::from((monitor_idx, idx));
+            }
+        }
+
+        None
+    }
+
+    /// Stop managing a window on its workspace and keep it visible on a monitor instead
+    pub fn stick_window(
+        &mut self,
+        window: Window,
+        scope: StickyScope,
+        monitor_idx: usize,
+    ) -> Result<()> {
+        if let Some((location_monitor_idx, workspace_idx)) = self.window_location(window.hwnd) {
+            self.monitors_mut()
+                .get_mut(location_monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .remove_window(window.hwnd)?;
+        }
+
+        self.monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .sticky_windows_mut()
+            .push(StickyWindow { window, scope });
+
+        {
+            let mut sticky_hwnds = STICKY_HWNDS.lock();
+            if !sticky_hwnds.contains(&window.hwnd) {
+                sticky_hwnds.push(window.hwnd);
+            }
+        }
+
+        window.restore()?;
+
+        tracing::info!("made window {} sticky", window.hwnd);
+
+        Ok(())
+    }
+
+    /// Stop keeping a window visible on every workspace, returning where it was kept
+    pub fn unstick_window(&mut self, hwnd: isize) -> Option<(usize, StickyWindow)> {
+        STICKY_HWNDS.lock().retain(|sticky| *sticky != hwnd);
+
+        let (monitor_idx, idx) = self.sticky_window_position(hwnd)?;
+        let sticky = self
+            .monitors_mut()
+            .get_mut(monitor_idx)?
+            .sticky_windows_mut()
+            .remove(idx);
+
+        Option::from((monitor_idx, sticky))
+    }
+
+    /// Make the focused window sticky, or return the foreground window to the focused workspace as
+    /// a floating window if it is already sticky
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_sticky(&mut self, scope: StickyScope) -> Result<()> {
+        let foreground = WindowsApi::foreground_window()?;
+
+        if let Some((monitor_idx, sticky)) = self.unstick_window(foreground) {
+            tracing::info!("returning sticky window {foreground} to its workspace");
+
+            self.focused_workspace_for_monitor_idx_mut(monitor_idx)?
+                .floating_windows_mut()
+                .push(sticky.window);
+
+            return self.update_focused_workspace(false);
+        }
+
+        let window = *self.focused_window()?;
+        let monitor_idx = self.focused_monitor_idx();
+
+        self.stick_window(window, scope, monitor_idx)?;
+        self.update_focused_workspace(false)?;
+
+        window.focus(self.mouse_follows_focus)
+    }
+
+    /// Keep a sticky window on the monitor it has been moved to
+    pub fn rehome_sticky_window(&mut self, window: Window) -> Result<()> {
+        let Some(target_idx) = self.monitor_idx_from_window(window) else {
+            return Ok(());
+        };
+
+        if let Some((monitor_idx, idx)) = self.sticky_window_position(window.hwnd) {
+            if monitor_idx != target_idx {
+                let sticky = self.monitors_mut()[monitor_idx]
+                    .sticky_windows_mut()
+                    .remove(idx);
+
+                self.monitors_mut()
+                    .get_mut(target_idx)
+                    .ok_or_else(|| anyhow!("there is no monitor"))?
+                    .sticky_windows_mut()
+                    .push(sticky);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bring the windows which are sticky on all monitors along when focus moves to another
+    /// monitor
+    pub fn follow_sticky_windows(&mut self, from: usize, to: usize) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+
+        let (Some(from_monitor), Some(to_monitor)) =
+            (self.monitors().get(from), self.monitors().get(to))
+        else {
+            return Ok(());
+        };
+
+        let from_work_area = *from_monitor.work_area_size();
+        let to_work_area = *to_monitor.work_area_size();
+
+        let following = from_monitor
+            .sticky_windows()
+            .iter()
+            .copied()
+            .filter(|sticky| matches!(sticky.scope, StickyScope::AllMonitors))
+            .collect::<Vec<_>>();
+
+        if following.is_empty() {
+            return Ok(());
+        }
+
+        self.monitors_mut()[from]
+            .sticky_windows_mut()
+            .retain(|sticky| !matches!(sticky.scope, StickyScope::AllMonitors));
+
+        for sticky in following {
+            // The window may already be there if it has been dragged across
+            if self.monitor_idx_from_window(sticky.window) != Option::from(to) {
+                let rect = WindowsApi::window_rect(sticky.window.hwnd())?;
+                sticky
+                    .window
+                    .set_position(&relocate(&rect, &from_work_area, &to_work_area), false)?;
+            }
+
+            self.monitors_mut()[to].sticky_windows_mut().push(sticky);
+        }
+
+        Ok(())
+    }
+}
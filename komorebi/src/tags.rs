@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::focus_history;
+use crate::monitor::Monitor;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::workspace::Workspace;
+use crate::WINDOW_TAGS;
+
+/// The tags which have been put on a window, not counting the name of the workspace it is on
+pub fn tags_for(hwnd: isize) -> BTreeSet<String> {
+    WINDOW_TAGS.lock().get(&hwnd).cloned().unwrap_or_default()
+}
+
+/// Remove every tag from a window which has been destroyed or unmanaged
+pub fn forget_window(hwnd: isize) {
+    WINDOW_TAGS.lock().remove(&hwnd);
+}
+
+/// Whether a window carries any of the tags, either explicitly or through the name of the
+/// workspace it is on
+fn carries_any(workspace_name: Option<&String>, window: &Window, tags: &[String]) -> bool {
+    workspace_name.is_some_and(|name| tags.contains(name))
+        || tags_for(window.hwnd).iter().any(|tag| tags.contains(tag))
+}
+
+/// The windows carrying one or more tags, gathered from the workspaces of a monitor onto a
+/// workspace of their own until that workspace is left
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TagView {
+    /// Tags whose windows are shown together
+    pub tags: Vec<String>,
+    /// Index of the workspace the windows have been gathered onto
+    pub workspace_idx: usize,
+    /// Index of the workspace which was focused before the view was shown
+    pub previous_workspace_idx: usize,
+    /// Where each gathered container and floating window belongs
+    pub homes: Vec<TagViewHome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum TagViewHome {
+    /// A container and its position on its workspace
+    Container {
+        id: String,
+        workspace_idx: usize,
+        container_idx: usize,
+    },
+    /// A floating window
+    Floating { hwnd: isize, workspace_idx: usize },
+}
+
+impl TagViewHome {
+    const fn workspace_idx(&self) -> usize {
+        match self {
+            Self::Container { workspace_idx, .. } | Self::Floating { workspace_idx, .. } => {
+                *workspace_idx
+            }
+        }
+    }
+
+    fn workspace_idx_mut(&mut self) -> &mut usize {
+        match self {
+            Self::Container { workspace_idx, .. } | Self::Floating { workspace_idx, .. } => {
+                workspace_idx
+            }
+        }
+    }
+}
+
+impl TagView {
+    /// Whether the view depends on a workspace staying where it is
+    pub fn involves(&self, idx: usize) -> bool {
+        self.workspace_idx == idx
+            || self.previous_workspace_idx == idx
+            || self.homes.iter().any(|home| home.workspace_idx() == idx)
+    }
+
+    /// Move along the indices after a workspace which has been removed
+    pub fn forget_workspace(&mut self, idx: usize) {
+        for workspace_idx in std::iter::once(&mut self.workspace_idx)
+            .chain(std::iter::once(&mut self.previous_workspace_idx))
+            .chain(self.homes.iter_mut().map(TagViewHome::workspace_idx_mut))
+        {
+            if *workspace_idx > idx {
+                *workspace_idx -= 1;
+            }
+        }
+    }
+}
+
+impl Monitor {
+    /// Gather the containers and floating windows carrying any of the tags onto a new workspace,
+    /// laid out like the workspace named after the first tag, and focus it
+    pub fn show_tag_view(&mut self, tags: Vec<String>) -> Result<()> {
+        let previous_workspace_idx = self.focused_workspace_idx();
+
+        let template_idx = self
+            .workspaces()
+            .iter()
+            .position(|workspace| workspace.name().as_ref() == tags.first())
+            .unwrap_or(previous_workspace_idx);
+
+        let mut view = Workspace::default();
+        view.set_name(Option::from(tags.join("+")));
+
+        if let Some(template) = self.workspaces().get(template_idx) {
+            view.set_layout(template.layout().clone());
+            view.set_layout_flip(template.layout_flip());
+            view.set_workspace_padding(template.workspace_padding());
+            view.set_container_padding(template.container_padding());
+        }
+
+        let mut homes = vec![];
+
+        for (workspace_idx, workspace) in self.workspaces_mut().iter_mut().enumerate() {
+            let name = workspace.name().clone();
+
+            // From the last to the first, so that the indices of the others don't change
+            let mut gathered = vec![];
+            for container_idx in (0..workspace.containers().len()).rev() {
+                let matches = workspace.containers()[container_idx]
+                    .windows()
+                    .iter()
+                    .any(|window| carries_any(name.as_ref(), window, &tags));
+
+                if matches {
+                    if let Some(container) = workspace.remove_container_by_idx(container_idx) {
+                        homes.push(TagViewHome::Container {
+                            id: container.id().clone(),
+                            workspace_idx,
+                            container_idx,
+                        });
+
+                        gathered.push(container);
+                    }
+                }
+            }
+
+            for container in gathered.into_iter().rev() {
+                view.add_container(container);
+            }
+
+            let (floating, remaining): (Vec<Window>, Vec<Window>) = workspace
+                .floating_windows()
+                .iter()
+                .copied()
+                .partition(|window| carries_any(name.as_ref(), window, &tags));
+
+            for window in &floating {
+                homes.push(TagViewHome::Floating {
+                    hwnd: window.hwnd,
+                    workspace_idx,
+                });
+            }
+
+            *workspace.floating_windows_mut() = remaining;
+            view.floating_windows_mut().extend(floating);
+
+            let container_count = workspace.containers().len();
+            if workspace.focused_container_idx() >= container_count {
+                workspace.focus_container(container_count.saturating_sub(1));
+            }
+        }
+
+        self.workspaces_mut().push_back(view);
+        let workspace_idx = self.workspaces().len() - 1;
+        self.focus_workspace(workspace_idx)?;
+
+        self.set_tag_view(Option::from(TagView {
+            tags,
+            workspace_idx,
+            previous_workspace_idx,
+            homes,
+        }));
+
+        Ok(())
+    }
+
+    /// Return the gathered windows to their workspaces and remove the workspace of the view;
+    /// windows opened while the view was shown go to the workspace which was focused before it,
+    /// returning the index of the removed workspace
+    pub fn dissolve_tag_view(&mut self) -> Result<Option<usize>> {
+        let Some(view) = self.tag_view().clone() else {
+            return Ok(None);
+        };
+
+        self.set_tag_view(None);
+
+        let focused_idx = self.focused_workspace_idx();
+        let Some(mut gathered) = self.workspaces_mut().remove(view.workspace_idx) else {
+            return Ok(None);
+        };
+
+        let previous_workspace_idx = if view.previous_workspace_idx > view.workspace_idx {
+            view.previous_workspace_idx - 1
+        } else {
+            view.previous_workspace_idx
+        };
+
+        // In reverse, so that the containers of each workspace go back from the first to the last
+        for home in view.homes.iter().rev() {
+            let workspace_idx = if home.workspace_idx() > view.workspace_idx {
+                home.workspace_idx() - 1
+            } else {
+                home.workspace_idx()
+            };
+
+            let Some(workspace) = self.workspaces_mut().get_mut(workspace_idx) else {
+                continue;
+            };
+
+            match home {
+                TagViewHome::Container {
+                    id, container_idx, ..
+                } => {
+                    let in_monocle = gathered
+                        .monocle_container()
+                        .as_ref()
+                        .is_some_and(|container| container.id() == id);
+
+                    // A gathered container may have been put in monocle while the view was shown
+                    let container = if in_monocle {
+                        gathered.monocle_container_mut().take()
+                    } else {
+                        gathered
+                            .containers()
+                            .iter()
+                            .position(|container| container.id() == id)
+                            .and_then(|idx| gathered.remove_container_by_idx(idx))
+                    };
+
+                    if let Some(container) = container {
+                        let container_idx = (*container_idx).min(workspace.containers().len());
+                        workspace.insert_container_at_idx(container_idx, container);
+                    }
+                }
+                TagViewHome::Floating { hwnd, .. } => {
+                    if let Some(idx) = gathered
+                        .floating_windows()
+                        .iter()
+                        .position(|window| window.hwnd == *hwnd)
+                    {
+                        let window = gathered.floating_windows_mut().remove(idx);
+                        workspace.floating_windows_mut().push(window);
+                    }
+                }
+            }
+        }
+
+        let fallback = self
+            .workspaces_mut()
+            .get_mut(previous_workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        for container in gathered.containers_mut().drain(..) {
+            fallback.add_container(container);
+        }
+
+        if let Some(container) = gathered.monocle_container_mut().take() {
+            fallback.add_container(container);
+        }
+
+        fallback
+            .floating_windows_mut()
+            .extend(gathered.floating_windows().iter().copied());
+
+        let focused_idx = match focused_idx {
+            idx if idx == view.workspace_idx => previous_workspace_idx,
+            idx if idx > view.workspace_idx => idx - 1,
+            idx => idx,
+        };
+
+        self.focus_workspace(focused_idx)?;
+
+        Ok(Option::from(view.workspace_idx))
+    }
+}
+
+impl WindowManager {
+    #[tracing::instrument(skip(self))]
+    pub fn tag_focused_window(&mut self, tag: &str) -> Result<()> {
+        let hwnd = self.focused_window()?.hwnd;
+        WINDOW_TAGS
+            .lock()
+            .entry(hwnd)
+            .or_default()
+            .insert(tag.to_string());
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn untag_focused_window(&mut self, tag: &str) -> Result<()> {
+        let hwnd = self.focused_window()?.hwnd;
+        let mut window_tags = WINDOW_TAGS.lock();
+
+        if let Some(tags) = window_tags.get_mut(&hwnd) {
+            tags.remove(tag);
+
+            if tags.is_empty() {
+                window_tags.remove(&hwnd);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Show the windows carrying any of the tags together on the focused monitor, replacing any
+    /// tag view already shown there; without any tags, the tag view is closed
+    #[tracing::instrument(skip(self))]
+    pub fn view_tags(&mut self, tags: Vec<String>) -> Result<()> {
+        if tags.iter().any(String::is_empty) {
+            bail!("tags cannot be empty");
+        }
+
+        let monitor_idx = self.focused_monitor_idx();
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        let monitor = self
+            .focused_monitor_mut()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        if let Some(workspace_idx) = monitor.dissolve_tag_view()? {
+            focus_history::forget_workspace(monitor_idx, workspace_idx);
+        }
+
+        if !tags.is_empty() {
+            monitor.show_tag_view(tags)?;
+        }
+
+        monitor.load_focused_workspace(mouse_follows_focus)?;
+        self.update_focused_workspace(mouse_follows_focus)
+    }
+
+    /// Close the tag views whose workspace has been left, returning their windows to the
+    /// workspaces they belong on
+    pub fn update_tag_views(&mut self) -> Result<()> {
+        let offset = self.work_area_offset;
+
+        for (monitor_idx, monitor) in self.monitors_mut().iter_mut().enumerate() {
+            let left = monitor
+                .tag_view()
+                .as_ref()
+                .is_some_and(|view| view.workspace_idx != monitor.focused_workspace_idx());
+
+            if !left {
+                continue;
+            }
+
+            if let Some(workspace_idx) = monitor.dissolve_tag_view()? {
+                tracing::info!("closing the tag view on monitor {monitor_idx}");
+                focus_history::forget_workspace(monitor_idx, workspace_idx);
+            }
+
+            monitor.load_focused_workspace(false)?;
+            monitor.update_focused_workspace(offset)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use komorebi_core::Rect;
+
+use crate::focus_history;
+use crate::monitor;
+use crate::monitor::VirtualSplit;
+use crate::static_config::monitor_config_assignments;
+use crate::window_manager::WindowManager;
+use crate::windows_api::WindowsApi;
+use crate::DEFAULT_MONITOR_CONFIGURATION;
+use crate::DISPLAY_INDEX_PREFERENCES;
+use crate::LAUNCH_WORKSPACE_RULES;
+use crate::MONITOR_CONFIGURATIONS;
+use crate::MONITOR_INDEX_PREFERENCES;
+use crate::WORKSPACE_HISTORY;
+use crate::WORKSPACE_RULES;
+
+const fn contains_point(rect: &Rect, x: i32, y: i32) -> bool {
+    x >= rect.left && x < rect.left + rect.right && y >= rect.top && y < rect.top + rect.bottom
+}
+
+/// Move the entries of a map keyed by monitor index, keeping the entry with the lowest index
+/// where several monitors have been joined into one
+fn remap_keys<T>(map: &mut HashMap<usize, T>, remap: impl Fn(usize) -> usize) {
+    let mut entries = map.drain().collect::<Vec<_>>();
+    entries.sort_by_key(|(idx, _)| *idx);
+
+    for (idx, value) in entries {
+        map.entry(remap(idx)).or_insert(value);
+    }
+}
+
+/// Move the monitor indices which are kept outside of the monitors themselves after virtual
+/// monitors have been inserted or removed
+fn remap_monitor_indices(remap: impl Fn(usize) -> usize) {
+    for (monitor_idx, _, _) in WORKSPACE_RULES.lock().values_mut() {
+        *monitor_idx = remap(*monitor_idx);
+    }
+
+    for (_, monitor_idx, _, _) in LAUNCH_WORKSPACE_RULES.lock().iter_mut() {
+        *monitor_idx = remap(*monitor_idx);
+    }
+
+    {
+        let mut history = WORKSPACE_HISTORY.lock();
+        for (monitor_idx, _) in history.iter_mut() {
+            *monitor_idx = remap(*monitor_idx);
+        }
+
+        history.dedup();
+    }
+
+    remap_keys(&mut MONITOR_INDEX_PREFERENCES.lock(), &remap);
+    remap_keys(&mut DISPLAY_INDEX_PREFERENCES.lock(), &remap);
+}
+
+impl WindowManager {
+    /// The monitor with this display handle which contains the point; displays which have not
+    /// been split have only one
+    pub fn monitor_idx_for_point(&self, hmonitor: isize, x: i32, y: i32) -> Option<usize> {
+        let mut candidates = self
+            .monitors()
+            .iter()
+            .enumerate()
+            .filter(|(_, monitor)| monitor.id() == hmonitor)
+            .peekable();
+
+        let (first, _) = *candidates.peek()?;
+
+        candidates
+            .find(|(_, monitor)| contains_point(monitor.size(), x, y))
+            .map_or(Option::from(first), |(idx, _)| Option::from(idx))
+    }
+
+    /// The index of the first part of the display a monitor is on
+    fn physical_monitor_idx(&self, idx: usize) -> Result<usize> {
+        let monitor = self
+            .monitors()
+            .get(idx)
+            .ok_or_else(|| anyhow!("there is no monitor at index {idx}"))?;
+
+        Ok(monitor
+            .virtual_split()
+            .as_ref()
+            .map_or(idx, |split| idx - split.part))
+    }
+
+    /// Split the display a monitor is on into side-by-side virtual monitors with the given
+    /// relative widths; the first keeps the workspaces of the display and the others start empty
+    #[tracing::instrument(skip(self))]
+    pub fn split_monitor(&mut self, idx: usize, widths: Vec<u32>) -> Result<()> {
+        if widths.len() < 2 || widths.contains(&0) {
+            bail!("a display can only be split into two or more virtual monitors with non-zero widths");
+        }
+
+        let idx = self.physical_monitor_idx(idx)?;
+
+        let existing = self.monitors()[idx].virtual_split().clone();
+        if let Some(split) = existing {
+            if split.widths == widths {
+                return Ok(());
+            }
+
+            self.merge_monitor(idx)?;
+        }
+
+        let parts = widths.len();
+        let physical = self.monitors()[idx].clone();
+        let reference = WindowsApi::monitor(physical.id())?;
+
+        for part in (0..parts).rev() {
+            let split = VirtualSplit {
+                part,
+                widths: widths.clone(),
+            };
+
+            let size = split.slice(reference.size());
+            let work_area_size = split.slice(reference.work_area_size());
+
+            if part == 0 {
+                let monitor = &mut self.monitors_mut()[idx];
+                monitor.set_size(size);
+                monitor.set_work_area_size(work_area_size);
+                monitor.set_virtual_split(Option::from(split));
+            } else {
+                let mut monitor =
+                    monitor::new(physical.id(), size, work_area_size, physical.name().clone());
+                monitor.set_device(physical.device().clone());
+                monitor.set_device_id(physical.device_id().clone());
+                monitor.set_work_area_offset(physical.work_area_offset());
+                monitor.set_virtual_split(Option::from(split));
+
+                self.monitors_mut().insert(idx + 1, monitor);
+            }
+        }
+
+        // Monitors after the display have moved along
+        let focused_idx = self.focused_monitor_idx();
+        if focused_idx > idx {
+            self.monitors.focus(focused_idx + parts - 1);
+        }
+
+        remap_monitor_indices(|i| if i > idx { i + parts - 1 } else { i });
+
+        tracing::info!("split monitor {idx} into {parts} virtual monitors");
+
+        self.retile_all(false)
+    }
+
+    /// Join the virtual monitors of a split display back together, moving the windows of each
+    /// workspace onto the workspace with the same index on the first virtual monitor
+    #[tracing::instrument(skip(self))]
+    pub fn merge_monitor(&mut self, idx: usize) -> Result<()> {
+        let idx = self.physical_monitor_idx(idx)?;
+
+        let Some(split) = self.monitors()[idx].virtual_split().clone() else {
+            return Ok(());
+        };
+
+        let parts = split.widths.len();
+
+        // Tag views only know their workspaces by index on their own monitor, so their windows
+        // are returned to where they belong before the workspaces are joined
+        for monitor_idx in idx..idx + parts {
+            if let Some(monitor) = self.monitors_mut().get_mut(monitor_idx) {
+                if let Some(workspace_idx) = monitor.dissolve_tag_view()? {
+                    focus_history::forget_workspace(monitor_idx, workspace_idx);
+                }
+            }
+        }
+
+        let focused_idx = self.focused_monitor_idx();
+
+        let mut removed = vec![];
+        for _ in 1..parts {
+            if self
+                .monitors()
+                .get(idx + 1)
+                .is_some_and(|monitor| monitor.virtual_split().is_some() && !monitor.is_physical())
+            {
+                removed.extend(self.monitors_mut().remove(idx + 1));
+            }
+        }
+
+        let reference = WindowsApi::monitor(self.monitors()[idx].id())?;
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        let monitor = &mut self.monitors_mut()[idx];
+        monitor.set_size(*reference.size());
+        monitor.set_work_area_size(*reference.work_area_size());
+        monitor.set_virtual_split(None);
+
+        for part in &removed {
+            monitor.ensure_workspace_count(part.workspaces().len());
+            monitor
+                .sticky_windows_mut()
+                .extend(part.sticky_windows().iter().copied());
+
+            for (workspace_idx, name) in part.workspace_names() {
+                monitor
+                    .workspace_names_mut()
+                    .entry(*workspace_idx)
+                    .or_insert_with(|| name.clone());
+            }
+
+            for (workspace, part_workspace) in
+                monitor.workspaces_mut().iter_mut().zip(part.workspaces())
+            {
+                if workspace.name().is_none() {
+                    workspace.set_name(part_workspace.name().clone());
+                }
+
+                for container in part_workspace.containers() {
+                    workspace.add_container(container.clone());
+                }
+
+                if let Some(container) = part_workspace.monocle_container() {
+                    workspace.add_container(container.clone());
+                }
+
+                workspace
+                    .floating_windows_mut()
+                    .extend(part_workspace.floating_windows().iter().copied());
+
+                // Resizes were made for the narrower virtual monitors and a different set of
+                // containers
+                if !part_workspace.containers().is_empty()
+                    || part_workspace.monocle_container().is_some()
+                {
+                    workspace.resize_dimensions_mut().clear();
+                }
+            }
+        }
+
+        // A virtual monitor which was focused hands its focused workspace over to the display
+        if let Some(part) = focused_idx
+            .checked_sub(idx + 1)
+            .and_then(|part_idx| removed.get(part_idx))
+        {
+            monitor.focus_workspace(part.focused_workspace_idx())?;
+            monitor.set_last_focused_workspace(part.last_focused_workspace());
+        }
+
+        monitor.load_focused_workspace(mouse_follows_focus)?;
+
+        if focused_idx > idx {
+            self.monitors
+                .focus(focused_idx.saturating_sub(removed.len()).max(idx));
+        }
+
+        let count = removed.len();
+        remap_monitor_indices(|i| {
+            if i > idx + count {
+                i - count
+            } else if i > idx {
+                idx
+            } else {
+                i
+            }
+        });
+
+        tracing::info!("merged the virtual monitors of monitor {idx}");
+
+        self.retile_all(false)
+    }
+
+    /// Split the focused display into the virtual monitors configured for it, or in half if there
+    /// are none, or join it back together if it is already split
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_virtual_monitors(&mut self) -> Result<()> {
+        let idx = self.physical_monitor_idx(self.focused_monitor_idx())?;
+
+        if self.monitors()[idx].virtual_split().is_some() {
+            return self.merge_monitor(idx);
+        }
+
+        let configs = MONITOR_CONFIGURATIONS.lock().clone();
+        let default = DEFAULT_MONITOR_CONFIGURATION.lock().clone();
+        let configured = monitor_config_assignments(&configs, default.as_ref(), self.monitors())
+            .get(idx)
+            .copied()
+            .flatten()
+            .and_then(|config| config.virtual_monitors.as_ref())
+            .is_some_and(|parts| parts.len() > 1);
+
+        if configured {
+            self.apply_monitor_configurations(Option::from(idx))
+        } else {
+            self.split_monitor(idx, vec![1, 1])
+        }
+    }
+}
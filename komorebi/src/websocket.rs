@@ -0,0 +1,220 @@
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use crossbeam_channel::Receiver;
+use parking_lot::Mutex;
+use tungstenite::handshake::server::ErrorResponse;
+use tungstenite::handshake::server::Request;
+use tungstenite::handshake::server::Response;
+use tungstenite::http::StatusCode;
+use tungstenite::Message;
+
+use komorebi_core::SocketMessage;
+
+use crate::notification_dispatcher;
+use crate::notification_dispatcher::websocket_subscription_key;
+use crate::notification_dispatcher::Transport;
+use crate::notify_subscribers;
+use crate::process_command::authenticate;
+use crate::process_command::authentication_required;
+use crate::process_command::ClientPermission;
+use crate::window_manager::WindowManager;
+use crate::Notification;
+use crate::NotificationEvent;
+use crate::WEBSOCKET_ALLOWED_ORIGINS;
+
+#[tracing::instrument]
+pub fn listen_for_commands_ws(wm: Arc<Mutex<WindowManager>>, port: usize) {
+    let address = format!("127.0.0.1:{port}");
+    let listener = TcpListener::bind(&address).expect("could not start websocket server");
+
+    std::thread::spawn(move || {
+        tracing::info!("listening on ws://{address}");
+        for client in listener.incoming() {
+            match client {
+                Ok(stream) => {
+                    let addr = stream
+                        .peer_addr()
+                        .expect("incoming connection should have an address")
+                        .to_string();
+
+                    let wm = wm.clone();
+
+                    // Each client streams notifications, so it gets a thread of its own
+                    std::thread::spawn(move || {
+                        tracing::info!("accepted websocket connection from {addr}");
+
+                        match read_commands_ws(&wm, stream, &addr) {
+                            Ok(()) => {}
+                            Err(error) => tracing::error!("{}", error),
+                        }
+
+                        tracing::info!("closed websocket connection from {addr}");
+                    });
+                }
+                Err(error) => {
+                    tracing::error!("{}", error);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Browsers send an Origin header with every WebSocket handshake, so a handshake which has one
+/// is only accepted if that origin has been allowed; otherwise any web page could connect
+#[allow(clippy::result_large_err)]
+fn check_origin(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    let Some(origin) = request.headers().get("Origin") else {
+        return Ok(response);
+    };
+
+    let origin = origin.to_str().ok();
+    let allowed_origins = WEBSOCKET_ALLOWED_ORIGINS.lock();
+
+    match origin {
+        Some(origin) if allowed_origins.iter().any(|allowed| allowed == origin) => Ok(response),
+        _ => {
+            tracing::warn!("rejecting websocket connection from origin {origin:?}");
+            let mut response = ErrorResponse::new(Option::from(String::from("origin not allowed")));
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            Err(response)
+        }
+    }
+}
+
+pub fn read_commands_ws(
+    wm: &Arc<Mutex<WindowManager>>,
+    stream: TcpStream,
+    addr: &str,
+) -> Result<()> {
+    let mut websocket = tungstenite::accept_hdr(stream, check_origin)
+        .map_err(|error| anyhow!("websocket handshake failed for {addr}: {error}"))?;
+
+    // Reads time out regularly so that queued notifications can be flushed to the client
+    websocket
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(50)))?;
+
+    // Full access always requires a token over WebSocket, since any local process (or an
+    // allowed web page) can connect to it
+    let mut permission = if authentication_required() {
+        None
+    } else {
+        Option::from(ClientPermission::ReadOnly)
+    };
+
+    let mut subscription: Option<(String, Receiver<String>)> = None;
+
+    let result = loop {
+        if let Some((_, notifications)) = &subscription {
+            for payload in notifications.try_iter() {
+                websocket.send(Message::Text(payload))?;
+            }
+        }
+
+        let text = match websocket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => break Ok(()),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error))
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(error) => break Err(error.into()),
+        };
+
+        let Ok(message) = SocketMessage::from_str(&text) else {
+            tracing::warn!("websocket client sent an invalid message: {addr}");
+            continue;
+        };
+
+        if let SocketMessage::Authenticate(ref token) = message {
+            if let Some(granted) = authenticate(token) {
+                tracing::info!("websocket client authenticated with {granted} access: {addr}");
+                permission = Option::from(granted);
+                continue;
+            }
+
+            tracing::warn!("websocket client failed to authenticate, disconnecting: {addr}");
+            break Ok(());
+        }
+
+        match permission {
+            None => {
+                tracing::warn!(
+                    "websocket client sent a message before authenticating, disconnecting: {addr}"
+                );
+                break Ok(());
+            }
+            Some(permission) if !permission.allows(&message) => {
+                tracing::warn!(
+                    "ignoring {message} from websocket client with {permission} access: {addr}"
+                );
+                continue;
+            }
+            Some(_) => {
+                tracing::info!("processing {message} from websocket client: {addr}");
+            }
+        }
+
+        if let SocketMessage::AddSubscriberStream(ref subscriber, mode) = message {
+            // A single slot, so that a slow client backs up into the dispatcher queue where
+            // the overflow policy applies
+            let (sender, receiver) = crossbeam_channel::bounded(1);
+            let key = websocket_subscription_key(subscriber);
+
+            notification_dispatcher::add_subscriber(key.clone(), Transport::Channel(sender), mode);
+            subscription = Option::from((key, receiver));
+
+            let wm = wm.lock();
+            notify_subscribers(&serde_json::to_string(&Notification {
+                event: NotificationEvent::Socket(message.clone()),
+                state: wm.as_ref().into(),
+            })?)?;
+
+            continue;
+        }
+
+        let mut reply = vec![];
+
+        {
+            let mut wm = wm.lock();
+
+            if wm.is_paused
+                && !matches!(
+                    message,
+                    SocketMessage::TogglePause | SocketMessage::State | SocketMessage::Stop
+                )
+            {
+                tracing::trace!("ignoring while paused");
+                continue;
+            }
+
+            wm.process_command(message.clone(), &mut reply)?;
+            notify_subscribers(&serde_json::to_string(&Notification {
+                event: NotificationEvent::Socket(message.clone()),
+                state: wm.as_ref().into(),
+            })?)?;
+        }
+
+        if !reply.is_empty() {
+            websocket.send(Message::Text(String::from_utf8_lossy(&reply).to_string()))?;
+        }
+    };
+
+    if let Some((key, _)) = subscription {
+        notification_dispatcher::remove_subscriber(&key);
+    }
+
+    result
+}
@@ -32,7 +32,9 @@ use komorebi_core::Rect;
 
 use crate::styles::ExtendedWindowStyle;
 use crate::styles::WindowStyle;
+use crate::tags;
 use crate::window_manager_event::WindowManagerEvent;
+use crate::window_marks;
 use crate::windows_api::WindowsApi;
 use crate::FLOAT_IDENTIFIERS;
 use crate::HIDDEN_HWNDS;
@@ -43,6 +45,7 @@ use crate::MAXIMIZED_HWNDS;
 use crate::NO_TITLEBAR;
 use crate::PERMAIGNORE_CLASSES;
 use crate::REGEX_IDENTIFIERS;
+use crate::STICKY_HWNDS;
 use crate::WSL2_UI_PROCESSES;
 
 #[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
@@ -97,7 +100,7 @@ impl Serialize for Window {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Window", 5)?;
+        let mut state = serializer.serialize_struct("Window", 7)?;
         state.serialize_field("hwnd", &self.hwnd)?;
         state.serialize_field(
             "title",
@@ -122,6 +125,8 @@ impl Serialize for Window {
             &WindowsApi::window_rect(self.hwnd())
                 .map_err(|_| S::Error::custom("could not get window rect"))?,
         )?;
+        state.serialize_field("marks", &window_marks::marks_for(self.hwnd))?;
+        state.serialize_field("tags", &tags::tags_for(self.hwnd))?;
         state.end()
     }
 }
@@ -195,6 +200,11 @@ impl Window {
     }
 
     pub fn hide(self) {
+        // Sticky windows stay visible whichever workspace is shown
+        if STICKY_HWNDS.lock().contains(&self.hwnd) {
+            return;
+        }
+
         let mut programmatically_hidden_hwnds = HIDDEN_HWNDS.lock();
         if !programmatically_hidden_hwnds.contains(&self.hwnd) {
             programmatically_hidden_hwnds.push(self.hwnd);
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -39,8 +40,11 @@ use komorebi_core::WindowContainerBehaviour;
 use crate::border::Border;
 use crate::container::Container;
 use crate::current_virtual_desktop;
+use crate::focus_history;
+use crate::journal::Journal;
 use crate::load_configuration;
 use crate::monitor::Monitor;
+use crate::notify_subscribers;
 use crate::ring::Ring;
 use crate::static_config::applications_configuration::SETTINGS_BY_APP;
 use crate::static_config::StaticConfig;
@@ -49,20 +53,51 @@ use crate::window_manager_event::WindowManagerEvent;
 use crate::windows_api::WindowsApi;
 use crate::winevent_listener;
 use crate::workspace::Workspace;
+use crate::workspace_groups;
+use crate::MonitorDetails;
+use crate::MonitorNotification;
+use crate::Notification;
+use crate::NotificationEvent;
 use crate::BORDER_HWND;
 use crate::BORDER_OVERFLOW_IDENTIFIERS;
 use crate::DATA_DIR;
 use crate::DISPLAY_INDEX_PREFERENCES;
 use crate::FLOAT_IDENTIFIERS;
 use crate::HOME_DIR;
+use crate::LAUNCH_WORKSPACE_RULES;
 use crate::LAYERED_WHITELIST;
 use crate::MANAGE_IDENTIFIERS;
 use crate::MONITOR_INDEX_PREFERENCES;
 use crate::NO_TITLEBAR;
 use crate::OBJECT_NAME_CHANGE_ON_LAUNCH;
 use crate::REMOVE_TITLEBARS;
+use crate::SCRATCHPAD_WINDOWS;
 use crate::TRAY_AND_MULTI_WINDOW_IDENTIFIERS;
 use crate::UNMANAGE_IDENTIFIERS;
+use crate::WORKSPACE_RULES;
+
+/// Move the workspace index of a rule along after a workspace has been removed, returning whether
+/// the rule should be kept, which it isn't if it was for the removed workspace
+fn keep_rule_after_removal(
+    rule_monitor_idx: usize,
+    rule_workspace_idx: &mut usize,
+    monitor_idx: usize,
+    workspace_idx: usize,
+) -> bool {
+    if rule_monitor_idx != monitor_idx {
+        return true;
+    }
+
+    if *rule_workspace_idx == workspace_idx {
+        return false;
+    }
+
+    if *rule_workspace_idx > workspace_idx {
+        *rule_workspace_idx -= 1;
+    }
+
+    true
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct PendingMoveOperation {
@@ -73,7 +108,7 @@ pub struct PendingMoveOperation {
 #[derive(Debug)]
 pub struct WindowManager {
     pub monitors: Ring<Monitor>,
-    pub monitor_cache: HashMap<usize, Monitor>,
+    pub monitor_cache: HashMap<String, Monitor>,
     pub incoming_events: Receiver<WindowManagerEvent>,
     pub command_listener: UnixListener,
     pub is_paused: bool,
@@ -89,6 +124,7 @@ pub struct WindowManager {
     pub has_pending_raise_op: bool,
     pub pending_move_op: Option<PendingMoveOperation>,
     pub already_moved_window_handles: Arc<Mutex<HashSet<isize>>>,
+    pub journal: Journal,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -113,6 +149,7 @@ pub struct State {
     pub name_change_on_launch_identifiers: Vec<IdWithIdentifier>,
     pub monitor_index_preferences: HashMap<usize, Rect>,
     pub display_index_preferences: HashMap<usize, String>,
+    pub marks: BTreeMap<String, isize>,
 }
 
 impl AsRef<Self> for WindowManager {
@@ -143,6 +180,7 @@ impl From<&WindowManager> for State {
             name_change_on_launch_identifiers: OBJECT_NAME_CHANGE_ON_LAUNCH.lock().clone(),
             monitor_index_preferences: MONITOR_INDEX_PREFERENCES.lock().clone(),
             display_index_preferences: DISPLAY_INDEX_PREFERENCES.lock().clone(),
+            marks: wm.marks(),
         }
     }
 }
@@ -209,6 +247,7 @@ impl WindowManager {
             has_pending_raise_op: false,
             pending_move_op: None,
             already_moved_window_handles: Arc::new(Mutex::new(HashSet::new())),
+            journal: Journal::default(),
         })
     }
 
@@ -305,39 +344,6 @@ impl WindowManager {
         Ok(())
     }
 
-    pub fn monitor_idx_in_direction(&self, direction: OperationDirection) -> Option<usize> {
-        let current_monitor_size = self.focused_monitor_size().ok()?;
-
-        for (idx, monitor) in self.monitors.elements().iter().enumerate() {
-            match direction {
-                OperationDirection::Left => {
-                    if monitor.size().left + monitor.size().right == current_monitor_size.left {
-                        return Option::from(idx);
-                    }
-                }
-                OperationDirection::Right => {
-                    if current_monitor_size.right + current_monitor_size.left == monitor.size().left
-                    {
-                        return Option::from(idx);
-                    }
-                }
-                OperationDirection::Up => {
-                    if monitor.size().top + monitor.size().bottom == current_monitor_size.top {
-                        return Option::from(idx);
-                    }
-                }
-                OperationDirection::Down => {
-                    if current_monitor_size.top + current_monitor_size.bottom == monitor.size().top
-                    {
-                        return Option::from(idx);
-                    }
-                }
-            }
-        }
-
-        None
-    }
-
     #[tracing::instrument(skip(self))]
     pub fn reconcile_monitors(&mut self) -> Result<()> {
         if self.pending_move_op.is_some() {
@@ -360,6 +366,7 @@ impl WindowManager {
 
         let mut orphaned_containers = vec![];
         let mut invalid_indices = vec![];
+        let mut monitor_notifications = vec![];
 
         for (i, invalid) in self
             .monitors()
@@ -379,7 +386,13 @@ impl WindowManager {
 
         for i in invalid_indices {
             if let Some(monitor) = self.monitors().get(i) {
-                self.monitor_cache.insert(i, monitor.clone());
+                tracing::info!("caching disconnected monitor {}", monitor.device_key());
+                monitor_notifications.push(MonitorNotification::MonitorRemoved(
+                    MonitorDetails::from_monitor(i, monitor),
+                ));
+
+                self.monitor_cache
+                    .insert(monitor.device_key(), monitor.clone());
             }
         }
 
@@ -412,25 +425,23 @@ impl WindowManager {
             let mut should_update = before_count != after_count;
 
             let reference = WindowsApi::monitor(monitor.id())?;
-            if reference.work_area_size() != monitor.work_area_size() {
-                monitor.set_work_area_size(Rect {
-                    left: reference.work_area_size().left,
-                    top: reference.work_area_size().top,
-                    right: reference.work_area_size().right,
-                    bottom: reference.work_area_size().bottom,
-                });
 
+            // Virtual monitors only cover their own part of the display
+            let (size, work_area_size) = match monitor.virtual_split() {
+                None => (*reference.size(), *reference.work_area_size()),
+                Some(split) => (
+                    split.slice(reference.size()),
+                    split.slice(reference.work_area_size()),
+                ),
+            };
+
+            if work_area_size != *monitor.work_area_size() {
+                monitor.set_work_area_size(work_area_size);
                 should_update = true;
             }
 
-            if reference.size() != monitor.size() {
-                monitor.set_size(Rect {
-                    left: reference.size().left,
-                    top: reference.size().top,
-                    right: reference.size().right,
-                    bottom: reference.size().bottom,
-                });
-
+            if size != *monitor.size() {
+                monitor.set_size(size);
                 should_update = true;
             }
 
@@ -440,37 +451,50 @@ impl WindowManager {
         }
 
         #[allow(clippy::needless_collect)]
-        let old_sizes = self
+        let old_keys = self
             .monitors()
             .iter()
-            .map(Monitor::size)
-            .copied()
+            .map(Monitor::device_key)
             .collect::<Vec<_>>();
 
         // Check for and add any new monitors that may have been plugged in
         WindowsApi::load_monitor_information(&mut self.monitors)?;
 
-        let mut check_cache = vec![];
+        let mut added = vec![];
 
         for (i, m) in self.monitors().iter().enumerate() {
-            if !old_sizes.contains(m.size()) {
-                check_cache.push(i);
+            if !old_keys.contains(&m.device_key()) {
+                monitor_notifications.push(MonitorNotification::MonitorAdded(
+                    MonitorDetails::from_monitor(i, m),
+                ));
+
+                added.push((i, m.device_key()));
             }
         }
 
-        for i in check_cache {
-            if let Some(cached) = self.monitor_cache.get(&i).cloned() {
-                if let Some(monitor) = self.monitors_mut().get_mut(i) {
-                    for (w_idx, workspace) in monitor.workspaces_mut().iter_mut().enumerate() {
-                        if let Some(cached_workspace) = cached.workspaces().get(w_idx) {
-                            workspace.set_layout(cached_workspace.layout().clone());
-                            workspace.set_layout_rules(cached_workspace.layout_rules().clone());
-                            workspace.set_layout_flip(cached_workspace.layout_flip());
-                            workspace.set_workspace_padding(cached_workspace.workspace_padding());
-                            workspace.set_container_padding(cached_workspace.container_padding());
-                        }
+        // In reverse, so that splitting a display doesn't move the monitors still to be restored
+        for (i, key) in added.into_iter().rev() {
+            let split_widths = self
+                .monitor_cache
+                .get(&format!("{key}#0"))
+                .and_then(|cached| cached.virtual_split().clone())
+                .map(|split| split.widths);
+
+            if let Some(cached) = self.monitor_cache.remove(&key) {
+                tracing::info!("restoring reconnected monitor {key}");
+                self.restore_cached_monitor(i, cached)?;
+            } else if let Some(widths) = split_widths {
+                tracing::info!("restoring reconnected split monitor {key}");
+                let parts = widths.len();
+                self.split_monitor(i, widths)?;
+
+                for part in 0..parts {
+                    if let Some(cached) = self.monitor_cache.remove(&format!("{key}#{part}")) {
+                        self.restore_cached_monitor(i + part, cached)?;
                     }
                 }
+            } else {
+                self.apply_monitor_configurations(Option::from(i))?;
             }
         }
 
@@ -484,9 +508,93 @@ impl WindowManager {
             self.retile_all(true)?;
         }
 
+        for notification in monitor_notifications {
+            notify_subscribers(&serde_json::to_string(&Notification {
+                event: NotificationEvent::Monitor(notification),
+                state: self.as_ref().into(),
+            })?)?;
+        }
+
         Ok(())
     }
 
+    /// Give a reconnected monitor back the workspaces it had when it was disconnected, taking
+    /// back any of their windows which still exist from wherever they were moved to
+    fn restore_cached_monitor(&mut self, idx: usize, mut cached: Monitor) -> Result<()> {
+        let mut cached_windows = vec![];
+        for workspace in cached.workspaces() {
+            for container in workspace.containers() {
+                cached_windows.extend(container.windows().iter().copied());
+            }
+
+            cached_windows.extend(workspace.floating_windows().iter().copied());
+        }
+
+        for window in &cached_windows {
+            if let Some((monitor_idx, workspace_idx)) = self.window_location(window.hwnd) {
+                self.monitors_mut()
+                    .get_mut(monitor_idx)
+                    .ok_or_else(|| anyhow!("there is no monitor"))?
+                    .workspaces_mut()
+                    .get_mut(workspace_idx)
+                    .ok_or_else(|| anyhow!("there is no workspace"))?
+                    .remove_window(window.hwnd)?;
+            }
+        }
+
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let monitor = self
+            .monitors_mut()
+            .get_mut(idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        monitor.ensure_workspace_count(cached.workspaces().len());
+        monitor
+            .workspace_names_mut()
+            .extend(cached.workspace_names_mut().drain());
+
+        for (w_idx, workspace) in monitor.workspaces_mut().iter_mut().enumerate() {
+            if let Some(cached_workspace) = cached.workspaces().get(w_idx) {
+                workspace.set_name(cached_workspace.name().clone());
+                workspace.set_layout(cached_workspace.layout().clone());
+                workspace.set_layout_rules(cached_workspace.layout_rules().clone());
+                workspace.set_layout_flip(cached_workspace.layout_flip());
+                workspace.set_workspace_padding(cached_workspace.workspace_padding());
+                workspace.set_container_padding(cached_workspace.container_padding());
+
+                for cached_container in cached_workspace.containers() {
+                    let mut container = Container::default();
+                    for window in cached_container.windows() {
+                        // Windows which were closed while the monitor was disconnected are gone
+                        if window.is_window() {
+                            container.add_window(*window);
+                        }
+                    }
+
+                    if !container.windows().is_empty() {
+                        container.focus_window(0);
+                        workspace.add_container(container);
+                    }
+                }
+
+                for window in cached_workspace.floating_windows() {
+                    if window.is_window() {
+                        workspace.floating_windows_mut().push(*window);
+                    }
+                }
+
+                let focused_container_idx = cached_workspace
+                    .focused_container_idx()
+                    .min(workspace.containers().len().saturating_sub(1));
+
+                workspace.focus_container(focused_container_idx);
+            }
+        }
+
+        monitor.focus_workspace(cached.focused_workspace_idx())?;
+        monitor.load_focused_workspace(mouse_follows_focus)
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[tracing::instrument(skip(self))]
     fn add_window_handle_to_move(
@@ -1020,6 +1128,14 @@ impl WindowManager {
             }
         }
 
+        // Windows hidden in a scratchpad are not on any workspace
+        for hwnd in SCRATCHPAD_WINDOWS.lock().values() {
+            let window = Window { hwnd: *hwnd };
+            if window.is_window() && self.window_location(*hwnd).is_none() {
+                window.restore()?;
+            }
+        }
+
         Ok(())
     }
 
@@ -2193,37 +2309,40 @@ impl WindowManager {
     pub fn focus_monitor(&mut self, idx: usize) -> Result<()> {
         tracing::info!("focusing monitor");
 
+        let previous_idx = self.focused_monitor_idx();
+
         if self.monitors().get(idx).is_some() {
+            if idx != previous_idx {
+                if let Ok(workspace_idx) = self.focused_workspace_idx_for_monitor_idx(previous_idx)
+                {
+                    focus_history::push_workspace(previous_idx, workspace_idx);
+                }
+            }
+
             self.monitors.focus(idx);
         } else {
             bail!("this is not a valid monitor index");
         }
 
-        Ok(())
+        self.follow_sticky_windows(previous_idx, idx)
     }
 
     pub fn monitor_idx_from_window(&mut self, window: Window) -> Option<usize> {
         let hmonitor = WindowsApi::monitor_from_window(window.hwnd());
+        let rect = WindowsApi::window_rect(window.hwnd()).unwrap_or_default();
 
-        for (i, monitor) in self.monitors().iter().enumerate() {
-            if monitor.id() == hmonitor {
-                return Option::from(i);
-            }
-        }
-
-        None
+        self.monitor_idx_for_point(
+            hmonitor,
+            rect.left + rect.right / 2,
+            rect.top + rect.bottom / 2,
+        )
     }
 
     pub fn monitor_idx_from_current_pos(&self) -> Option<usize> {
-        let hmonitor = WindowsApi::monitor_from_point(WindowsApi::cursor_pos().ok()?);
+        let cursor_pos = WindowsApi::cursor_pos().ok()?;
+        let hmonitor = WindowsApi::monitor_from_point(cursor_pos);
 
-        for (i, monitor) in self.monitors().iter().enumerate() {
-            if monitor.id() == hmonitor {
-                return Option::from(i);
-            }
-        }
-
-        None
+        self.monitor_idx_for_point(hmonitor, cursor_pos.x, cursor_pos.y)
     }
 
     pub fn focused_workspace(&self) -> Result<&Workspace> {
@@ -2276,13 +2395,20 @@ impl WindowManager {
         tracing::info!("focusing workspace");
 
         let mouse_follows_focus = self.mouse_follows_focus;
+        let monitor_idx = self.focused_monitor_idx();
         let monitor = self
             .focused_monitor_mut()
             .ok_or_else(|| anyhow!("there is no workspace"))?;
 
+        let previous_idx = monitor.focused_workspace_idx();
+
         monitor.focus_workspace(idx)?;
         monitor.load_focused_workspace(mouse_follows_focus)?;
 
+        if previous_idx != idx {
+            focus_history::push_workspace(monitor_idx, previous_idx);
+        }
+
         self.update_focused_workspace(false)
     }
 
@@ -2308,16 +2434,78 @@ impl WindowManager {
         tracing::info!("adding new workspace");
 
         let mouse_follows_focus = self.mouse_follows_focus;
+        let monitor_idx = self.focused_monitor_idx();
         let monitor = self
             .focused_monitor_mut()
             .ok_or_else(|| anyhow!("there is no workspace"))?;
 
+        focus_history::push_workspace(monitor_idx, monitor.focused_workspace_idx());
+
         monitor.focus_workspace(monitor.new_workspace_idx())?;
         monitor.load_focused_workspace(mouse_follows_focus)?;
 
         self.update_focused_workspace(self.mouse_follows_focus)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn set_dynamic_workspaces(&mut self, monitor_idx: usize, enable: bool) -> Result<()> {
+        tracing::info!("setting dynamic workspaces");
+
+        self.monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .set_dynamic_workspaces(enable);
+
+        self.update_dynamic_workspaces()
+    }
+
+    /// Remove the empty workspaces which have been left on monitors with dynamic workspaces,
+    /// keeping the workspace indices used by rules and the workspace history in step
+    pub fn update_dynamic_workspaces(&mut self) -> Result<()> {
+        for monitor_idx in 0..self.monitors().len() {
+            let removed = self
+                .monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .update_dynamic_workspaces();
+
+            let device_key = self.monitors()[monitor_idx].device_key();
+
+            for workspace_idx in removed {
+                tracing::info!("removing empty workspace {workspace_idx} on monitor {monitor_idx}");
+
+                focus_history::forget_workspace(monitor_idx, workspace_idx);
+                workspace_groups::forget_workspace(&device_key, workspace_idx);
+
+                // Rules for the removed workspace are dropped rather than left to send windows
+                // to whichever workspace takes its place
+                WORKSPACE_RULES
+                    .lock()
+                    .retain(|_, (rule_monitor_idx, rule_workspace_idx, _)| {
+                        keep_rule_after_removal(
+                            *rule_monitor_idx,
+                            rule_workspace_idx,
+                            monitor_idx,
+                            workspace_idx,
+                        )
+                    });
+
+                LAUNCH_WORKSPACE_RULES.lock().retain_mut(
+                    |(_, rule_monitor_idx, rule_workspace_idx, _)| {
+                        keep_rule_after_removal(
+                            *rule_monitor_idx,
+                            rule_workspace_idx,
+                            monitor_idx,
+                            workspace_idx,
+                        )
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn focused_container(&self) -> Result<&Container> {
         self.focused_workspace()?
             .focused_container()
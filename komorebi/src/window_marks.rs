@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::WINDOW_MARKS;
+
+/// The marks which have been put on a window, in alphabetical order
+pub fn marks_for(hwnd: isize) -> Vec<String> {
+    let mut marks = WINDOW_MARKS
+        .lock()
+        .iter()
+        .filter(|(_, marked)| **marked == hwnd)
+        .map(|(mark, _)| mark.clone())
+        .collect::<Vec<_>>();
+
+    marks.sort();
+    marks
+}
+
+/// Remove every mark from a window which has been destroyed or unmanaged
+pub fn forget_window(hwnd: isize) {
+    WINDOW_MARKS.lock().retain(|_, marked| *marked != hwnd);
+}
+
+fn marked_hwnd(mark: &str) -> Result<isize> {
+    WINDOW_MARKS
+        .lock()
+        .get(mark)
+        .copied()
+        .ok_or_else(|| anyhow!("there is no window with the mark {mark}"))
+}
+
+impl WindowManager {
+    /// Put a mark on the focused window, moving it from any other window which had it
+    #[tracing::instrument(skip(self))]
+    pub fn mark_focused_window(&mut self, mark: &str) -> Result<()> {
+        let hwnd = self.focused_window()?.hwnd;
+        WINDOW_MARKS.lock().insert(mark.to_string(), hwnd);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn focus_mark(&mut self, mark: &str) -> Result<()> {
+        let hwnd = marked_hwnd(mark)?;
+        if self.window_location(hwnd).is_none() {
+            forget_window(hwnd);
+            bail!("the window with the mark {mark} is no longer managed");
+        }
+
+        self.focus_window_by_hwnd(hwnd)
+    }
+
+    /// Every mark and the window it is on, in alphabetical order
+    pub fn marks(&self) -> BTreeMap<String, isize> {
+        WINDOW_MARKS
+            .lock()
+            .iter()
+            .filter(|(_, hwnd)| self.window_location(**hwnd).is_some())
+            .map(|(mark, hwnd)| (mark.clone(), *hwnd))
+            .collect()
+    }
+
+    /// The monitor, workspace, container and window indices of a tiled window
+    fn tiled_window_position(&self, hwnd: isize) -> Option<(usize, usize, usize, usize)> {
+        for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+                for (container_idx, container) in workspace.containers().iter().enumerate() {
+                    if let Some(window_idx) = container.idx_for_window(hwnd) {
+                        return Option::from((
+                            monitor_idx,
+                            workspace_idx,
+                            container_idx,
+                            window_idx,
+                        ));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Swap the focused window with the marked window, wherever it is, keeping focus in the same
+    /// place so that the marked window is brought to it
+    #[tracing::instrument(skip(self))]
+    pub fn swap_with_mark(&mut self, mark: &str) -> Result<()> {
+        let focused = self.focused_window()?.hwnd;
+        let marked = marked_hwnd(mark)?;
+
+        if focused == marked {
+            return Ok(());
+        }
+
+        let focused_position = self
+            .tiled_window_position(focused)
+            .ok_or_else(|| anyhow!("the focused window is not tiled"))?;
+        let marked_position = self
+            .tiled_window_position(marked)
+            .ok_or_else(|| anyhow!("the window with the mark {mark} is not tiled"))?;
+
+        for ((monitor_idx, workspace_idx, container_idx, window_idx), hwnd) in
+            [(focused_position, marked), (marked_position, focused)]
+        {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?;
+            let visible = monitor.focused_workspace_idx() == workspace_idx;
+
+            let container = monitor
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .containers_mut()
+                .get_mut(container_idx)
+                .ok_or_else(|| anyhow!("there is no container"))?;
+
+            let window = container
+                .windows_mut()
+                .get_mut(window_idx)
+                .ok_or_else(|| anyhow!("there is no window"))?;
+
+            *window = Window { hwnd };
+
+            if visible {
+                container.load_focused_window()?;
+                container.restore()?;
+            } else {
+                container.hide(None)?;
+            }
+        }
+
+        self.retile_all(false)?;
+        self.update_focused_workspace(self.mouse_follows_focus)
+    }
+}
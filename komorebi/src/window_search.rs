@@ -0,0 +1,179 @@
+use color_eyre::Result;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use komorebi_core::SearchMode;
+
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::WINDOW_LAST_FOCUSED;
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum WindowLayer {
+    Tiling,
+    Monocle,
+    Floating,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindowSearchResult {
+    pub hwnd: isize,
+    pub layer: WindowLayer,
+    pub monitor_idx: usize,
+    pub workspace_idx: usize,
+    /// Not set for monocle containers and floating windows
+    pub container_idx: Option<usize>,
+    /// The index of the window in its container's stack; not set for floating windows
+    pub window_idx: Option<usize>,
+    pub title: String,
+    pub exe: String,
+    pub class: String,
+    /// Milliseconds since the Unix epoch
+    pub last_focused: Option<u64>,
+}
+
+/// Scores a fuzzy match of the query as a case-insensitive subsequence of the candidate, with a
+/// bonus for consecutive characters
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate = candidate.to_lowercase();
+    let mut characters = candidate.chars();
+    let mut score = 0;
+    let mut streak = 0;
+
+    for q in query.to_lowercase().chars() {
+        let mut skipped = false;
+
+        loop {
+            match characters.next() {
+                Some(c) if c == q => {
+                    streak = if skipped { 1 } else { streak + 1 };
+                    score += streak;
+                    break;
+                }
+                Some(_) => skipped = true,
+                None => return None,
+            }
+        }
+    }
+
+    Option::from(score)
+}
+
+impl WindowManager {
+    /// Every managed window across all monitors and workspaces, filtered by the query if there is
+    /// one; fuzzy results are ordered by score and all others by most recently focused
+    pub fn search_windows(
+        &self,
+        query: Option<&str>,
+        mode: SearchMode,
+    ) -> Result<Vec<WindowSearchResult>> {
+        let mut results = vec![];
+
+        let last_focused = WINDOW_LAST_FOCUSED.lock().clone();
+
+        let mut push = |window: &Window,
+                        layer: WindowLayer,
+                        monitor_idx: usize,
+                        workspace_idx: usize,
+                        container_idx: Option<usize>,
+                        window_idx: Option<usize>| {
+            // Windows which have gone away since the last event are left out
+            if let (Ok(title), Ok(exe), Ok(class)) = (window.title(), window.exe(), window.class())
+            {
+                results.push(WindowSearchResult {
+                    hwnd: window.hwnd,
+                    layer,
+                    monitor_idx,
+                    workspace_idx,
+                    container_idx,
+                    window_idx,
+                    title,
+                    exe,
+                    class,
+                    last_focused: last_focused.get(&window.hwnd).copied(),
+                });
+            }
+        };
+
+        for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+                for (container_idx, container) in workspace.containers().iter().enumerate() {
+                    for (window_idx, window) in container.windows().iter().enumerate() {
+                        push(
+                            window,
+                            WindowLayer::Tiling,
+                            monitor_idx,
+                            workspace_idx,
+                            Option::from(container_idx),
+                            Option::from(window_idx),
+                        );
+                    }
+                }
+
+                if let Some(container) = workspace.monocle_container() {
+                    for (window_idx, window) in container.windows().iter().enumerate() {
+                        push(
+                            window,
+                            WindowLayer::Monocle,
+                            monitor_idx,
+                            workspace_idx,
+                            None,
+                            Option::from(window_idx),
+                        );
+                    }
+                }
+
+                for window in workspace.floating_windows() {
+                    push(
+                        window,
+                        WindowLayer::Floating,
+                        monitor_idx,
+                        workspace_idx,
+                        None,
+                        None,
+                    );
+                }
+            }
+        }
+
+        let haystack = |result: &WindowSearchResult| {
+            format!("{} {} {}", result.title, result.exe, result.class)
+        };
+
+        match (query, mode) {
+            (None | Some(""), _) => {
+                results.sort_by(|a, b| b.last_focused.cmp(&a.last_focused));
+            }
+            (Some(query), SearchMode::Substring) => {
+                let query = query.to_lowercase();
+                results.retain(|result| haystack(result).to_lowercase().contains(&query));
+                results.sort_by(|a, b| b.last_focused.cmp(&a.last_focused));
+            }
+            (Some(query), SearchMode::Regex) => {
+                let re = Regex::new(query)?;
+                results.retain(|result| re.is_match(&haystack(result)));
+                results.sort_by(|a, b| b.last_focused.cmp(&a.last_focused));
+            }
+            (Some(query), SearchMode::Fuzzy) => {
+                let mut scored = results
+                    .into_iter()
+                    .filter_map(|result| {
+                        fuzzy_score(query, &haystack(&result)).map(|score| (score, result))
+                    })
+                    .collect::<Vec<_>>();
+
+                scored.sort_by(|(a_score, a), (b_score, b)| {
+                    b_score
+                        .cmp(a_score)
+                        .then_with(|| b.last_focused.cmp(&a.last_focused))
+                });
+
+                results = scored.into_iter().map(|(_, result)| result).collect();
+            }
+        }
+
+        Ok(results)
+    }
+}
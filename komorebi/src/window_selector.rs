@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use std::time::Duration;
+use std::time::Instant;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use regex::Regex;
+
+use komorebi_core::config_generation::IdWithIdentifier;
+use komorebi_core::config_generation::MatchingStrategy;
+use komorebi_core::SocketMessage;
+use komorebi_core::WindowSelector;
+
+use crate::window::should_act;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::workspace::Workspace;
+use crate::LAUNCH_COMMANDS;
+use crate::LAUNCH_WORKSPACE_RULES;
+
+fn workspace_windows(workspace: &Workspace) -> Vec<Window> {
+    let mut windows = vec![];
+
+    for container in workspace.containers() {
+        windows.extend(container.windows().iter().copied());
+    }
+
+    if let Some(container) = workspace.monocle_container() {
+        windows.extend(container.windows().iter().copied());
+    }
+
+    windows.extend(workspace.floating_windows().iter().copied());
+
+    windows
+}
+
+/// Whether the window matches the identifier, using the same rules as the static configuration
+pub fn window_matches(window: Window, identifier: &IdWithIdentifier) -> Result<bool> {
+    let mut identifier = identifier.clone();
+    if identifier.matching_strategy.is_none() {
+        identifier.matching_strategy = Option::from(MatchingStrategy::Legacy);
+    }
+
+    let mut regex_identifiers = HashMap::new();
+    if matches!(identifier.matching_strategy, Some(MatchingStrategy::Regex)) {
+        regex_identifiers.insert(identifier.id.clone(), Regex::new(&identifier.id)?);
+    }
+
+    // Windows which have gone away since the last event can't be matched
+    let (Ok(title), Ok(exe), Ok(class), Ok(path)) =
+        (window.title(), window.exe(), window.class(), window.path())
+    else {
+        return Ok(false);
+    };
+
+    Ok(should_act(
+        &title,
+        &exe,
+        &class,
+        &path,
+        &[identifier],
+        &regex_identifiers,
+    ))
+}
+
+impl WindowManager {
+    pub fn selected_windows(&self, selector: &WindowSelector) -> Result<Vec<Window>> {
+        let mut selected = vec![];
+
+        match selector {
+            WindowSelector::Hwnd(hwnd) => {
+                if self.window_location(*hwnd).is_none() {
+                    bail!("there is no managed window with the handle {hwnd}");
+                }
+
+                selected.push(Window { hwnd: *hwnd });
+            }
+            WindowSelector::Application(identifier) => {
+                for monitor in self.monitors() {
+                    for workspace in monitor.workspaces() {
+                        for window in workspace_windows(workspace) {
+                            if window_matches(window, identifier)? {
+                                selected.push(window);
+                            }
+                        }
+                    }
+                }
+            }
+            WindowSelector::Workspace(monitor_idx, workspace_idx) => {
+                let workspace = self
+                    .monitors()
+                    .get(*monitor_idx)
+                    .ok_or_else(|| anyhow!("there is no monitor"))?
+                    .workspaces()
+                    .get(*workspace_idx)
+                    .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+                selected.extend(workspace_windows(workspace));
+            }
+            WindowSelector::NamedWorkspace(name) => {
+                let workspace = self
+                    .monitors()
+                    .iter()
+                    .flat_map(|monitor| monitor.workspaces())
+                    .find(|workspace| workspace.name().as_deref() == Some(name.as_str()))
+                    .ok_or_else(|| anyhow!("there is no workspace named {name}"))?;
+
+                selected.extend(workspace_windows(workspace));
+            }
+        }
+
+        Ok(selected)
+    }
+
+    /// The monitor and workspace indices of the workspace which manages this window
+    pub fn window_location(&self, hwnd: isize) -> Option<(usize, usize)> {
+        for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+                if workspace.contains_window(hwnd) {
+                    return Option::from((monitor_idx, workspace_idx));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Switch to the monitor and workspace of a managed window, then focus it, selecting it in
+    /// its stack if necessary
+    #[tracing::instrument(skip(self))]
+    pub fn focus_window_by_hwnd(&mut self, hwnd: isize) -> Result<()> {
+        let (monitor_idx, workspace_idx) = self
+            .window_location(hwnd)
+            .ok_or_else(|| anyhow!("there is no managed window with the handle {hwnd}"))?;
+
+        if self.focused_monitor_idx() != monitor_idx {
+            self.focus_monitor(monitor_idx)?;
+        }
+
+        if self.focused_workspace_idx_for_monitor_idx(monitor_idx)? != workspace_idx {
+            self.focus_workspace(workspace_idx)?;
+        }
+
+        let workspace = self.focused_workspace_mut()?;
+        if workspace.container_for_window(hwnd).is_some() {
+            workspace.focus_container_by_window(hwnd)?;
+        }
+
+        // Windows in monocle containers and floating windows are focused directly
+        Window { hwnd }.focus(self.mouse_follows_focus)
+    }
+
+    /// Run a window command on every window matched by the selector by focusing each of them in
+    /// turn, before returning focus to the window that was focused beforehand
+    #[tracing::instrument(skip(self))]
+    pub fn run_with_selector(
+        &mut self,
+        selector: &WindowSelector,
+        message: &SocketMessage,
+    ) -> Result<()> {
+        if !message.accepts_selector() {
+            bail!("{message} cannot be used with a window selector");
+        }
+
+        let selected = self.selected_windows(selector)?;
+        if selected.is_empty() {
+            tracing::info!("no windows matched the selector");
+            return Ok(());
+        }
+
+        let origin_monitor_idx = self.focused_monitor_idx();
+        let origin_workspace_idx =
+            self.focused_workspace_idx_for_monitor_idx(origin_monitor_idx)?;
+        let origin_hwnd = self.focused_window().ok().map(|window| window.hwnd);
+
+        for window in selected {
+            match message {
+                SocketMessage::Close => {
+                    window.close()?;
+                    continue;
+                }
+                SocketMessage::Minimize => {
+                    window.minimize();
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Earlier commands may have moved or closed this window
+            let Some((monitor_idx, workspace_idx)) = self.window_location(window.hwnd) else {
+                continue;
+            };
+
+            // Floating windows are not in a container, so only commands which act on the
+            // foreground window can target them
+            let allow_floating = matches!(message, SocketMessage::ToggleFloat);
+
+            if !self.focus_selected_window(monitor_idx, workspace_idx, window, allow_floating)? {
+                tracing::debug!("skipping {message} for window {}", window.hwnd);
+                continue;
+            }
+
+            self.process_command(message.clone(), std::io::sink())?;
+        }
+
+        if self.focused_monitor_idx() != origin_monitor_idx {
+            self.focus_monitor(origin_monitor_idx)?;
+        }
+
+        if self.focused_workspace_idx_for_monitor_idx(origin_monitor_idx)? != origin_workspace_idx {
+            self.focus_workspace(origin_workspace_idx)?;
+        }
+
+        if let Some(hwnd) = origin_hwnd {
+            let mouse_follows_focus = self.mouse_follows_focus;
+            let workspace = self.focused_workspace_mut()?;
+            if workspace.container_for_window(hwnd).is_some() {
+                workspace.focus_container_by_window(hwnd)?;
+                Window { hwnd }.focus(mouse_follows_focus)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns false if the window can't be targeted by commands which act on the focused
+    /// container, such as windows in a monocle container
+    fn focus_selected_window(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        window: Window,
+        allow_floating: bool,
+    ) -> Result<bool> {
+        if self.focused_monitor_idx() != monitor_idx {
+            self.focus_monitor(monitor_idx)?;
+        }
+
+        if self.focused_workspace_idx_for_monitor_idx(monitor_idx)? != workspace_idx {
+            self.focus_workspace(workspace_idx)?;
+        }
+
+        let workspace = self.focused_workspace_mut()?;
+        if workspace.container_for_window(window.hwnd).is_some() {
+            workspace.focus_container_by_window(window.hwnd)?;
+        } else if !allow_floating
+            || !workspace
+                .floating_windows()
+                .iter()
+                .any(|floating| floating.hwnd == window.hwnd)
+        {
+            return Ok(false);
+        }
+
+        window.focus(self.mouse_follows_focus)?;
+
+        Ok(true)
+    }
+}
+
+/// How long a launched application has to open its window for the one-shot workspace rule to apply
+const LAUNCH_WORKSPACE_RULE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// CREATE_NO_WINDOW, so that launching through cmd.exe does not flash a console
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+impl WindowManager {
+    /// Focus the next managed window matching the identifier, or run the named launch command from
+    /// the static config if there are none; the first new matching window can optionally be sent
+    /// to a workspace
+    #[tracing::instrument(skip(self))]
+    pub fn run_or_raise(
+        &mut self,
+        identifier: &IdWithIdentifier,
+        launch: Option<&str>,
+        workspace: Option<(usize, usize)>,
+    ) -> Result<()> {
+        let matching = self.selected_windows(&WindowSelector::Application(identifier.clone()))?;
+
+        if !matching.is_empty() {
+            // Repeatedly raising an application with several windows cycles through them
+            let focused = self.focused_window().ok().map(|window| window.hwnd);
+            let next = matching
+                .iter()
+                .position(|window| Some(window.hwnd) == focused)
+                .map_or(0, |idx| (idx + 1) % matching.len());
+
+            return self.focus_window_by_hwnd(matching[next].hwnd);
+        }
+
+        let Some(launch) = launch else {
+            tracing::info!("no windows matched and there is no command to launch");
+            return Ok(());
+        };
+
+        // Only commands from the static config can be launched, so that nothing which can send
+        // a socket message is able to run arbitrary commands
+        let command = LAUNCH_COMMANDS
+            .lock()
+            .get(launch)
+            .cloned()
+            .ok_or_else(|| anyhow!("there is no launch command with the name {launch}"))?;
+
+        if let Some((monitor_idx, workspace_idx)) = workspace {
+            let monitor = self
+                .monitors()
+                .get(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor with that index"))?;
+
+            if workspace_idx >= monitor.workspaces().len() {
+                bail!("there is no workspace with that index");
+            }
+
+            let mut rules = LAUNCH_WORKSPACE_RULES.lock();
+            rules.push((
+                identifier.clone(),
+                monitor_idx,
+                workspace_idx,
+                Instant::now() + LAUNCH_WORKSPACE_RULE_TIMEOUT,
+            ));
+        }
+
+        tracing::info!("launching: {command}");
+
+        Command::new("cmd.exe")
+            .args(["/C", &command])
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn()?;
+
+        Ok(())
+    }
+
+    /// Move a newly managed window to the workspace given by a pending run-or-raise launch, if
+    /// it matches one
+    pub fn enforce_launch_workspace_rules(&mut self, window: Window) -> Result<()> {
+        let target = {
+            let mut rules = LAUNCH_WORKSPACE_RULES.lock();
+            let now = Instant::now();
+            rules.retain(|(_, _, _, deadline)| *deadline > now);
+
+            let mut matched = None;
+            for (idx, (identifier, _, _, _)) in rules.iter().enumerate() {
+                if window_matches(window, identifier)? {
+                    matched = Option::from(idx);
+                    break;
+                }
+            }
+
+            matched.map(|idx| {
+                let (_, monitor_idx, workspace_idx, _) = rules.remove(idx);
+                (monitor_idx, workspace_idx)
+            })
+        };
+
+        let Some((target_monitor_idx, target_workspace_idx)) = target else {
+            return Ok(());
+        };
+
+        let Some(origin) = self.window_location(window.hwnd) else {
+            return Ok(());
+        };
+
+        if origin != (target_monitor_idx, target_workspace_idx) {
+            tracing::info!(
+                "moving launched window to monitor {target_monitor_idx}, workspace {target_workspace_idx}"
+            );
+
+            let target_monitor = self
+                .monitors()
+                .get(target_monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor with that index"))?;
+
+            // The workspace was checked when the rule was created, but dynamic workspaces may
+            // have been removed since then
+            if target_workspace_idx >= target_monitor.workspaces().len() {
+                tracing::warn!("launch target workspace no longer exists, not moving window");
+                return Ok(());
+            }
+
+            let work_area = *target_monitor.work_area_size();
+
+            self.monitors_mut()
+                .get_mut(origin.0)
+                .ok_or_else(|| anyhow!("there is no monitor with that index"))?
+                .workspaces_mut()
+                .get_mut(origin.1)
+                .ok_or_else(|| anyhow!("there is no workspace with that index"))?
+                .remove_window(window.hwnd)?;
+
+            window.hide();
+
+            let target_monitor = self
+                .monitors_mut()
+                .get_mut(target_monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor with that index"))?;
+
+            let target_workspace = target_monitor
+                .workspaces_mut()
+                .get_mut(target_workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace with that index"))?;
+
+            let mut window = window;
+            if window.should_float() {
+                target_workspace.floating_windows_mut().push(window);
+                window.center(&work_area)?;
+            } else {
+                target_workspace.new_container_for_window(window)?;
+            }
+
+            self.update_focused_workspace(false)?;
+        }
+
+        self.focus_window_by_hwnd(window.hwnd)
+    }
+}
@@ -58,6 +58,7 @@ use windows::Win32::System::Threading::QueryFullProcessImageNameW;
 use windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS;
 use windows::Win32::System::Threading::PROCESS_NAME_WIN32;
 use windows::Win32::System::Threading::PROCESS_QUERY_INFORMATION;
+use windows::Win32::System::IO::CancelSynchronousIo;
 use windows::Win32::UI::HiDpi::GetDpiForMonitor;
 use windows::Win32::UI::HiDpi::SetProcessDpiAwarenessContext;
 use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
@@ -686,6 +687,10 @@ impl WindowsApi {
         unsafe { CloseHandle(handle) }.process()
     }
 
+    pub fn cancel_synchronous_io(thread: HANDLE) -> Result<()> {
+        unsafe { CancelSynchronousIo(thread) }.process()
+    }
+
     pub fn process_handle(process_id: u32) -> Result<HANDLE> {
         Self::open_process(PROCESS_QUERY_INFORMATION, false, process_id)
     }
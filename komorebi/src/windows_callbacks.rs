@@ -78,7 +78,12 @@ pub extern "system" fn enum_display_monitor(
         }
     }
 
-    let current_index = monitors.elements().len();
+    // Virtual monitors share a display device with the first part of their display
+    let current_index = monitors
+        .elements()
+        .iter()
+        .filter(|monitor| monitor.is_physical())
+        .count();
 
     if let Ok(mut m) = WindowsApi::monitor(hmonitor.0) {
         #[allow(clippy::cast_possible_truncation)]
@@ -487,6 +487,12 @@ impl Workspace {
         false
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.containers().is_empty()
+            && self.monocle_container().is_none()
+            && self.floating_windows().is_empty()
+    }
+
     pub fn is_focused_window_monocle_or_maximized(&self) -> Result<bool> {
         let hwnd = WindowsApi::foreground_window()?;
 
@@ -0,0 +1,136 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use color_eyre::eyre::anyhow;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use crate::focus_history;
+use crate::static_config::WorkspaceReference;
+use crate::window_manager::WindowManager;
+use crate::WORKSPACE_GROUPS;
+
+pub fn is_workspace_group(name: &str) -> bool {
+    WORKSPACE_GROUPS.lock().contains_key(name)
+}
+
+/// Forget a workspace which has been removed from the groups which refer to it by index, moving
+/// along the indices of the workspaces after it
+pub fn forget_workspace(device_key: &str, workspace_idx: usize) {
+    for (name, group) in WORKSPACE_GROUPS.lock().iter_mut() {
+        if let Some(WorkspaceReference::Index(idx)) = group.get_mut(device_key) {
+            if *idx == workspace_idx {
+                tracing::info!("removing monitor {device_key} from workspace group {name}");
+                group.remove(device_key);
+            } else if *idx > workspace_idx {
+                *idx -= 1;
+            }
+        }
+    }
+}
+
+impl WindowManager {
+    /// The monitor and workspace indices of a workspace group, with the focused monitor last;
+    /// monitors which are not connected are left out
+    fn workspace_group_targets(&self, name: &str) -> Result<Vec<(usize, usize)>> {
+        let group = WORKSPACE_GROUPS
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("there is no workspace group named {name}"))?;
+
+        let mut targets = vec![];
+
+        for (device_key, reference) in group {
+            let Some(monitor_idx) = self.monitor_idx_for_device_key(&device_key) else {
+                tracing::warn!(
+                    "skipping disconnected monitor {device_key} of workspace group {name}"
+                );
+                continue;
+            };
+
+            let monitor = &self.monitors()[monitor_idx];
+
+            let workspace_idx = match reference {
+                WorkspaceReference::Index(idx) => idx,
+                WorkspaceReference::Name(ref workspace_name) => monitor
+                    .workspaces()
+                    .iter()
+                    .position(|workspace| workspace.name().as_ref() == Some(workspace_name))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "there is no workspace named {workspace_name} on monitor {device_key}"
+                        )
+                    })?,
+            };
+
+            targets.push((monitor_idx, workspace_idx));
+        }
+
+        if targets.is_empty() {
+            bail!("none of the monitors of workspace group {name} are connected");
+        }
+
+        // The focused monitor is restored last so that it keeps the foreground window
+        let focused_monitor_idx = self.focused_monitor_idx();
+        targets.sort_by_key(|(monitor_idx, _)| (*monitor_idx == focused_monitor_idx, *monitor_idx));
+
+        Ok(targets)
+    }
+
+    /// Focus the workspaces of a workspace group on all of its monitors at once, hiding the
+    /// workspaces which are left on every monitor before restoring any of the focused ones
+    #[tracing::instrument(skip(self))]
+    pub fn focus_workspace_group(&mut self, name: &str) -> Result<()> {
+        tracing::info!("focusing workspace group");
+
+        let targets = self.workspace_group_targets(name)?;
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let focused_monitor_idx = self.focused_monitor_idx();
+        let offset = self.work_area_offset;
+
+        let mut wait_for_minimization = false;
+
+        for (monitor_idx, workspace_idx) in &targets {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+            let previous_idx = monitor.focused_workspace_idx();
+            if previous_idx != *workspace_idx {
+                monitor.set_last_focused_workspace(Option::from(previous_idx));
+
+                if *monitor_idx == focused_monitor_idx {
+                    focus_history::push_workspace(*monitor_idx, previous_idx);
+                }
+            }
+
+            monitor.focus_workspace(*workspace_idx)?;
+            wait_for_minimization |= monitor.hide_unfocused_workspaces()?;
+        }
+
+        if wait_for_minimization {
+            sleep(Duration::from_millis(200));
+        }
+
+        for (monitor_idx, _) in &targets {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+            monitor
+                .focused_workspace()
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .restore(mouse_follows_focus && *monitor_idx == focused_monitor_idx)?;
+
+            // The focused monitor is updated along with the focused window below
+            if *monitor_idx != focused_monitor_idx {
+                monitor.update_focused_workspace(offset)?;
+            }
+        }
+
+        self.update_focused_workspace(mouse_follows_focus)
+    }
+}
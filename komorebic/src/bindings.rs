@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use heck::ToSnakeCase;
+use serde_json::Value;
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum BindingsLanguage {
+    /// TypeScript, with a client for the WebSocket server started with 'komorebi --ws-port'
+    #[value(name = "ts", alias = "typescript")]
+    TypeScript,
+    /// Python, with a client for the WebSocket server started with 'komorebi --ws-port'
+    Python,
+}
+
+/// Type definitions and message constructors built from the JSON Schemas that the daemon
+/// generates for `SocketMessage` and `Notification`
+pub struct Bindings {
+    definitions: BTreeMap<String, Value>,
+    messages: Vec<(String, Option<Value>)>,
+}
+
+impl Bindings {
+    pub fn from_schemas(socket_schema: &str, notification_schema: &str) -> Result<Self> {
+        let mut definitions = BTreeMap::new();
+
+        for schema in [socket_schema, notification_schema] {
+            let mut root: Value = serde_json::from_str(schema)?;
+            let root_object = root
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("the schema is not an object"))?;
+
+            if let Some(Value::Object(nested)) = root_object.remove("definitions") {
+                definitions.extend(nested);
+            }
+
+            root_object.remove("$schema");
+
+            let title = root_object
+                .get("title")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("the schema has no title"))?
+                .to_string();
+
+            definitions.insert(title, root);
+        }
+
+        let variants = definitions
+            .get("SocketMessage")
+            .and_then(|schema| schema.get("oneOf"))
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("the SocketMessage schema has no variants"))?;
+
+        let mut messages = vec![];
+        for variant in variants {
+            let name = variant
+                .pointer("/properties/type/enum/0")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("a SocketMessage variant has no type"))?;
+
+            messages.push((
+                name.to_string(),
+                variant.pointer("/properties/content").cloned(),
+            ));
+        }
+
+        Ok(Self {
+            definitions,
+            messages,
+        })
+    }
+
+    pub fn generate(&self, language: BindingsLanguage) -> Result<String> {
+        match language {
+            BindingsLanguage::TypeScript => self.typescript(),
+            BindingsLanguage::Python => self.python(),
+        }
+    }
+
+    fn typescript(&self) -> Result<String> {
+        let mut output = String::from(TYPESCRIPT_HEADER);
+
+        for (name, schema) in &self.definitions {
+            if let Some(description) = description(schema) {
+                writeln!(output, "/** {} */", description.replace("*/", "*\\/"))?;
+            }
+
+            writeln!(output, "export type {name} = {};\n", ts_type(schema))?;
+        }
+
+        writeln!(output, "export const SocketMessages = {{")?;
+        for (name, content) in &self.messages {
+            match content {
+                None => writeln!(
+                    output,
+                    "  {name}: (): SocketMessage => ({{ type: \"{name}\" }}),"
+                )?,
+                Some(content) => writeln!(
+                    output,
+                    "  {name}: (content: {}): SocketMessage => ({{ type: \"{name}\", content }}),",
+                    ts_type(content)
+                )?,
+            }
+        }
+        writeln!(output, "}};\n")?;
+
+        output.push_str(TYPESCRIPT_CLIENT);
+
+        Ok(output)
+    }
+
+    fn python(&self) -> Result<String> {
+        let mut output = String::from(PYTHON_HEADER);
+
+        for (name, schema) in &self.definitions {
+            if let Some(description) = description(schema) {
+                writeln!(output, "# {description}")?;
+            }
+
+            match schema.get("properties") {
+                Some(Value::Object(properties)) => {
+                    let required = required(schema);
+                    writeln!(output, "{name} = TypedDict(\"{name}\", {{")?;
+                    for (field, field_schema) in properties {
+                        let field_type = py_type(field_schema);
+                        if required.contains(&field.as_str()) {
+                            writeln!(output, "    \"{field}\": {field_type},")?;
+                        } else {
+                            writeln!(output, "    \"{field}\": NotRequired[{field_type}],")?;
+                        }
+                    }
+                    writeln!(output, "}})\n")?;
+                }
+                _ => writeln!(output, "{name}: TypeAlias = {}\n", py_type(schema))?,
+            }
+        }
+
+        writeln!(output, "\nclass SocketMessages:")?;
+        for (name, content) in &self.messages {
+            let function = name.to_snake_case();
+            writeln!(output, "    @staticmethod")?;
+            match content {
+                None => {
+                    writeln!(output, "    def {function}() -> \"SocketMessage\":")?;
+                    writeln!(output, "        return {{\"type\": \"{name}\"}}\n")?;
+                }
+                Some(content) => {
+                    writeln!(
+                        output,
+                        "    def {function}(content: {}) -> \"SocketMessage\":",
+                        py_type(content)
+                    )?;
+                    writeln!(
+                        output,
+                        "        return {{\"type\": \"{name}\", \"content\": content}}\n"
+                    )?;
+                }
+            }
+        }
+
+        output.push_str(PYTHON_CLIENT);
+
+        Ok(output)
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(|description| description.replace('\n', " "))
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn reference(schema: &Value) -> Option<&str> {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .map(|reference| reference.trim_start_matches("#/definitions/"))
+}
+
+fn variants(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+}
+
+fn union(types: impl Iterator<Item = String>, separator: &str) -> String {
+    let mut unique: Vec<String> = vec![];
+    for t in types {
+        if !unique.contains(&t) {
+            unique.push(t);
+        }
+    }
+
+    unique.join(separator)
+}
+
+fn ts_type(schema: &Value) -> String {
+    if let Some(reference) = reference(schema) {
+        return reference.to_string();
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        return union(all.iter().map(ts_type), " & ");
+    }
+
+    if let Some(variants) = variants(schema) {
+        return union(variants.iter().map(ts_type), " | ");
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return union(values.iter().map(Value::to_string), " | ");
+    }
+
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+
+    match schema.get("type") {
+        Some(Value::Array(types)) => union(
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|t| ts_primitive(schema, t)),
+            " | ",
+        ),
+        Some(Value::String(t)) => ts_primitive(schema, t),
+        _ => String::from("unknown"),
+    }
+}
+
+fn ts_primitive(schema: &Value, primitive: &str) -> String {
+    match primitive {
+        "string" => String::from("string"),
+        "integer" | "number" => String::from("number"),
+        "boolean" => String::from("boolean"),
+        "null" => String::from("null"),
+        "array" => match schema.get("items") {
+            Some(Value::Array(items)) => {
+                format!(
+                    "[{}]",
+                    items.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+                )
+            }
+            Some(items) => format!("({})[]", ts_type(items)),
+            None => String::from("unknown[]"),
+        },
+        "object" => match (schema.get("properties"), schema.get("additionalProperties")) {
+            (Some(Value::Object(properties)), _) => {
+                let required = required(schema);
+                let fields = properties
+                    .iter()
+                    .map(|(field, field_schema)| {
+                        let optional = if required.contains(&field.as_str()) {
+                            ""
+                        } else {
+                            "?"
+                        };
+
+                        format!("{field}{optional}: {}", ts_type(field_schema))
+                    })
+                    .collect::<Vec<_>>();
+
+                format!("{{ {} }}", fields.join("; "))
+            }
+            (_, Some(additional @ Value::Object(_))) => {
+                format!("{{ [key: string]: {} }}", ts_type(additional))
+            }
+            _ => String::from("Record<string, unknown>"),
+        },
+        _ => String::from("unknown"),
+    }
+}
+
+fn py_literal(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("None"),
+        Value::Bool(true) => String::from("True"),
+        Value::Bool(false) => String::from("False"),
+        value => value.to_string(),
+    }
+}
+
+fn py_union(types: impl Iterator<Item = String>) -> String {
+    let types = union(types, ", ");
+    if types.contains(", ") {
+        format!("Union[{types}]")
+    } else {
+        types
+    }
+}
+
+fn py_type(schema: &Value) -> String {
+    if let Some(reference) = reference(schema) {
+        return format!("\"{reference}\"");
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        return if all.len() == 1 {
+            py_type(&all[0])
+        } else {
+            String::from("Any")
+        };
+    }
+
+    if let Some(variants) = variants(schema) {
+        return py_union(variants.iter().map(py_type));
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return format!(
+            "Literal[{}]",
+            values.iter().map(py_literal).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    if let Some(value) = schema.get("const") {
+        return format!("Literal[{}]", py_literal(value));
+    }
+
+    match schema.get("type") {
+        Some(Value::Array(types)) => py_union(
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|t| py_primitive(schema, t)),
+        ),
+        Some(Value::String(t)) => py_primitive(schema, t),
+        _ => String::from("Any"),
+    }
+}
+
+fn py_primitive(schema: &Value, primitive: &str) -> String {
+    match primitive {
+        "string" => String::from("str"),
+        "integer" => String::from("int"),
+        "number" => String::from("float"),
+        "boolean" => String::from("bool"),
+        "null" => String::from("None"),
+        "array" => match schema.get("items") {
+            Some(Value::Array(items)) => format!(
+                "Tuple[{}]",
+                items.iter().map(py_type).collect::<Vec<_>>().join(", ")
+            ),
+            Some(items) => format!("List[{}]", py_type(items)),
+            None => String::from("List[Any]"),
+        },
+        "object" => match schema.get("additionalProperties") {
+            Some(additional @ Value::Object(_)) if schema.get("properties").is_none() => {
+                format!("Dict[str, {}]", py_type(additional))
+            }
+            _ => String::from("Dict[str, Any]"),
+        },
+        _ => String::from("Any"),
+    }
+}
+
+const TYPESCRIPT_HEADER: &str = r#"// Generated by 'komorebic generate-bindings --lang ts'; do not edit by hand
+//
+// KomorebiClient talks to the WebSocket server that komorebi only starts when it is run with
+// --ws-port; it does not use komorebi.sock. Commands other than queries need one of the tokens
+// set in tcp.tokens in komorebi.json.
+
+"#;
+
+const TYPESCRIPT_CLIENT: &str = r#"/**
+ * A client for the WebSocket server started with 'komorebi --ws-port'; pass one of the tokens set
+ * in tcp.tokens in komorebi.json to send commands other than queries
+ */
+export class KomorebiClient {
+  constructor(
+    private readonly url: string,
+    private readonly token?: string,
+  ) {}
+
+  private connect(): Promise<WebSocket> {
+    return new Promise((resolve, reject) => {
+      const socket = new WebSocket(this.url);
+      socket.onopen = () => {
+        if (this.token !== undefined) {
+          socket.send(JSON.stringify(SocketMessages.Authenticate(this.token)));
+        }
+        resolve(socket);
+      };
+      socket.onerror = () => reject(new Error(`could not connect to ${this.url}`));
+    });
+  }
+
+  async send(message: SocketMessage): Promise<void> {
+    const socket = await this.connect();
+    socket.send(JSON.stringify(message));
+    socket.close();
+  }
+
+  async query<T = unknown>(message: SocketMessage): Promise<T> {
+    const socket = await this.connect();
+    return new Promise((resolve, reject) => {
+      socket.onmessage = (event) => {
+        socket.onclose = null;
+        socket.close();
+        try {
+          resolve(JSON.parse(event.data) as T);
+        } catch (error) {
+          reject(error);
+        }
+      };
+      socket.onclose = () => reject(new Error("the connection closed before a response was received"));
+      socket.send(JSON.stringify(message));
+    });
+  }
+
+  state(): Promise<State> {
+    return this.query<State>(SocketMessages.State());
+  }
+
+  /** Calls onNotification for every notification until the returned function is called */
+  async subscribe(
+    name: string,
+    onNotification: (notification: Notification) => void,
+  ): Promise<() => void> {
+    const socket = await this.connect();
+    socket.onmessage = (event) => onNotification(JSON.parse(event.data) as Notification);
+    socket.send(JSON.stringify(SocketMessages.AddSubscriberStream([name, "Full"])));
+    return () => socket.close();
+  }
+}
+"#;
+
+const PYTHON_HEADER: &str = r#"# Generated by 'komorebic generate-bindings --lang python'; do not edit by hand
+#
+# Requires Python 3.8 or later and the 'typing_extensions' package. KomorebiClient also requires
+# the 'websocket-client' package, and talks to the WebSocket server that komorebi only starts when
+# it is run with --ws-port; it does not use komorebi.sock. Commands other than queries need one of
+# the tokens set in tcp.tokens in komorebi.json.
+
+import json
+from typing import Any, Dict, Iterator, List, Literal, Optional, Tuple, TypedDict, Union
+
+from typing_extensions import NotRequired, TypeAlias
+
+"#;
+
+const PYTHON_CLIENT: &str = r#"
+class KomorebiClient:
+    """A client for the WebSocket server started with 'komorebi --ws-port'
+
+    Requires the 'websocket-client' package; pass one of the tokens set in tcp.tokens in
+    komorebi.json to send commands other than queries
+    """
+
+    def __init__(self, url: str, token: Optional[str] = None, timeout: float = 5.0) -> None:
+        self.url = url
+        self.token = token
+        self.timeout = timeout
+
+    def _connect(self) -> Any:
+        from websocket import create_connection
+
+        connection = create_connection(self.url, timeout=self.timeout)
+        if self.token is not None:
+            connection.send(json.dumps(SocketMessages.authenticate(self.token)))
+
+        return connection
+
+    def send(self, message: "SocketMessage") -> None:
+        connection = self._connect()
+        try:
+            connection.send(json.dumps(message))
+        finally:
+            connection.close()
+
+    def query(self, message: "SocketMessage") -> Any:
+        connection = self._connect()
+        try:
+            connection.send(json.dumps(message))
+            return json.loads(connection.recv())
+        finally:
+            connection.close()
+
+    def state(self) -> "State":
+        return self.query(SocketMessages.state())
+
+    def subscribe(self, name: str) -> Iterator["Notification"]:
+        connection = self._connect()
+        connection.settimeout(None)
+        try:
+            connection.send(json.dumps(SocketMessages.add_subscriber_stream((name, "Full"))))
+            while True:
+                yield json.loads(connection.recv())
+        finally:
+            connection.close()
+"#;
@@ -11,6 +11,7 @@ use std::net::Shutdown;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -41,6 +42,8 @@ use windows::Win32::UI::WindowsAndMessaging::SW_RESTORE;
 use derive_ahk::AhkFunction;
 use derive_ahk::AhkLibrary;
 use komorebi_core::config_generation::ApplicationConfigurationGenerator;
+use komorebi_core::config_generation::IdWithIdentifier;
+use komorebi_core::config_generation::MatchingStrategy;
 use komorebi_core::ApplicationIdentifier;
 use komorebi_core::Axis;
 use komorebi_core::CycleDirection;
@@ -50,11 +53,22 @@ use komorebi_core::HidingBehaviour;
 use komorebi_core::MoveBehaviour;
 use komorebi_core::OperationBehaviour;
 use komorebi_core::OperationDirection;
+use komorebi_core::OverflowPolicy;
 use komorebi_core::Rect;
+use komorebi_core::SearchMode;
 use komorebi_core::Sizing;
+use komorebi_core::SnapshotScope;
 use komorebi_core::SocketMessage;
 use komorebi_core::StateQuery;
+use komorebi_core::StickyScope;
+use komorebi_core::SubscriptionMode;
 use komorebi_core::WindowKind;
+use komorebi_core::WindowSelector;
+
+use crate::bindings::Bindings;
+use crate::bindings::BindingsLanguage;
+
+mod bindings;
 
 lazy_static! {
     static ref HAS_CUSTOM_CONFIG_HOME: AtomicBool = AtomicBool::new(false);
@@ -153,6 +167,7 @@ gen_enum_subcommand_args! {
     Focus: OperationDirection,
     Move: OperationDirection,
     CycleFocus: CycleDirection,
+    CycleFocusHistory: CycleDirection,
     CycleMove: CycleDirection,
     CycleMoveToWorkspace: CycleDirection,
     CycleSendToWorkspace: CycleDirection,
@@ -171,6 +186,7 @@ gen_enum_subcommand_args! {
     WindowHidingBehaviour: HidingBehaviour,
     CrossMonitorMoveBehaviour: MoveBehaviour,
     UnmanagedWindowOperationBehaviour: OperationBehaviour,
+    SubscriberOverflowPolicy: OverflowPolicy,
 }
 
 macro_rules! gen_target_subcommand_args {
@@ -218,6 +234,12 @@ gen_named_target_subcommand_args! {
     ClearNamedWorkspaceLayoutRules
 }
 
+#[derive(Parser, AhkFunction)]
+struct FocusWorkspaceGroup {
+    /// Name of a workspace group defined in the static configuration file
+    group: String,
+}
+
 // Thanks to @danielhenrymantilla for showing me how to use cfg_attr with an optional argument like
 // this on the Rust Programming Language Community Discord Server
 macro_rules! gen_workspace_subcommand_args {
@@ -454,6 +476,14 @@ struct EnsureWorkspaces {
     workspace_count: usize,
 }
 
+#[derive(Parser, AhkFunction)]
+struct DynamicWorkspaces {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    #[clap(value_enum)]
+    boolean_state: BooleanState,
+}
+
 #[derive(Parser, AhkFunction)]
 struct EnsureNamedWorkspaces {
     /// Monitor index (zero-indexed)
@@ -462,6 +492,237 @@ struct EnsureNamedWorkspaces {
     names: Vec<String>,
 }
 
+#[derive(Parser, AhkFunction)]
+struct Run {
+    /// Name of a macro defined in the static configuration file
+    name: String,
+    /// Parameters to substitute for $1, $2, ... in the macro
+    parameters: Vec<String>,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum SelectCommand {
+    Close,
+    Minimize,
+    ToggleFloat,
+    Promote,
+    /// Requires an operation direction argument
+    Move,
+    /// Requires an operation direction argument
+    Stack,
+    Unstack,
+    /// Requires a workspace index argument
+    MoveToWorkspace,
+    /// Requires a workspace index argument
+    SendToWorkspace,
+    /// Requires a workspace name argument
+    MoveToNamedWorkspace,
+    /// Requires a workspace name argument
+    SendToNamedWorkspace,
+    /// Requires a monitor index argument
+    MoveToMonitor,
+    /// Requires a monitor index argument
+    SendToMonitor,
+    /// Requires a mark argument
+    Mark,
+    /// Requires a tag argument
+    Tag,
+}
+
+#[derive(Parser, AhkFunction)]
+struct Select {
+    /// Command to run on every selected window
+    #[clap(value_enum)]
+    command: SelectCommand,
+    /// Argument for the command: an operation direction, a workspace or monitor index, a workspace name, a mark or a tag
+    argument: Option<String>,
+    /// Select the managed window with this handle
+    #[clap(long)]
+    hwnd: Option<isize>,
+    /// Select managed windows by this kind of application identifier
+    #[clap(long, value_enum, requires = "id")]
+    identifier: Option<ApplicationIdentifier>,
+    /// Identifier as a string
+    #[clap(long, requires = "identifier")]
+    id: Option<String>,
+    /// Matching strategy for the identifier (default: legacy)
+    #[clap(long, value_enum)]
+    strategy: Option<MatchingStrategy>,
+    /// Select every window on the workspace at this index (requires --monitor)
+    #[clap(long, requires = "monitor")]
+    workspace: Option<usize>,
+    /// Monitor index (zero-indexed) for --workspace
+    #[clap(long, requires = "workspace")]
+    monitor: Option<usize>,
+    /// Select every window on the workspace with this name
+    #[clap(long)]
+    named_workspace: Option<String>,
+}
+
+impl Select {
+    fn selector(&self) -> Result<WindowSelector> {
+        if let Some(hwnd) = self.hwnd {
+            return Ok(WindowSelector::Hwnd(hwnd));
+        }
+
+        if let (Some(kind), Some(id)) = (self.identifier, &self.id) {
+            return Ok(WindowSelector::Application(IdWithIdentifier {
+                kind,
+                id: id.clone(),
+                matching_strategy: self.strategy,
+            }));
+        }
+
+        if let (Some(monitor), Some(workspace)) = (self.monitor, self.workspace) {
+            return Ok(WindowSelector::Workspace(monitor, workspace));
+        }
+
+        if let Some(name) = &self.named_workspace {
+            return Ok(WindowSelector::NamedWorkspace(name.clone()));
+        }
+
+        bail!("a selector is required: --hwnd, --identifier and --id, --monitor and --workspace, or --named-workspace")
+    }
+
+    fn message(&self) -> Result<SocketMessage> {
+        let argument = || {
+            self.argument
+                .clone()
+                .ok_or_else(|| anyhow!("this command requires an argument"))
+        };
+
+        let direction = || -> Result<OperationDirection> {
+            OperationDirection::from_str(&argument()?)
+                .map_err(|_| anyhow!("the argument must be an operation direction"))
+        };
+
+        let index = || -> Result<usize> {
+            argument()?
+                .parse()
+                .map_err(|_| anyhow!("the argument must be an index"))
+        };
+
+        Ok(match self.command {
+            SelectCommand::Close => SocketMessage::Close,
+            SelectCommand::Minimize => SocketMessage::Minimize,
+            SelectCommand::ToggleFloat => SocketMessage::ToggleFloat,
+            SelectCommand::Promote => SocketMessage::Promote,
+            SelectCommand::Move => SocketMessage::MoveWindow(direction()?),
+            SelectCommand::Stack => SocketMessage::StackWindow(direction()?),
+            SelectCommand::Unstack => SocketMessage::UnstackWindow,
+            SelectCommand::MoveToWorkspace => {
+                SocketMessage::MoveContainerToWorkspaceNumber(index()?)
+            }
+            SelectCommand::SendToWorkspace => {
+                SocketMessage::SendContainerToWorkspaceNumber(index()?)
+            }
+            SelectCommand::MoveToNamedWorkspace => {
+                SocketMessage::MoveContainerToNamedWorkspace(argument()?)
+            }
+            SelectCommand::SendToNamedWorkspace => {
+                SocketMessage::SendContainerToNamedWorkspace(argument()?)
+            }
+            SelectCommand::MoveToMonitor => SocketMessage::MoveContainerToMonitorNumber(index()?),
+            SelectCommand::SendToMonitor => SocketMessage::SendContainerToMonitorNumber(index()?),
+            SelectCommand::Mark => SocketMessage::MarkWindow(argument()?),
+            SelectCommand::Tag => SocketMessage::TagWindow(argument()?),
+        })
+    }
+}
+
+#[derive(Parser, AhkFunction)]
+struct SearchWindows {
+    /// Filter windows by their title, exe and class
+    query: Option<String>,
+    /// How the query is matched
+    #[clap(long, value_enum, default_value_t = SearchMode::Substring)]
+    mode: SearchMode,
+}
+
+#[derive(Parser, AhkFunction)]
+struct FocusWindowByHwnd {
+    /// Handle of a managed window
+    hwnd: isize,
+}
+
+#[derive(Parser, AhkFunction)]
+struct Mark {
+    /// Name of the mark
+    mark: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct FocusMark {
+    /// Name of the mark
+    mark: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct SwapWithMark {
+    /// Name of the mark
+    mark: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct Tag {
+    /// Name of the tag
+    tag: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct Untag {
+    /// Name of the tag
+    tag: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct ViewTags {
+    /// Names of the tags whose windows should be shown together (close the tag view if none)
+    tags: Vec<String>,
+}
+
+#[derive(Parser, AhkFunction)]
+struct SendToScratchpad {
+    /// Name of the scratchpad
+    name: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct ScratchpadToggle {
+    /// Name of the scratchpad
+    name: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct ScratchpadRule {
+    /// Name of the scratchpad
+    name: String,
+    #[clap(value_enum)]
+    identifier: ApplicationIdentifier,
+    /// Identifier as a string
+    id: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct RunOrRaise {
+    #[clap(value_enum)]
+    identifier: ApplicationIdentifier,
+    /// Identifier as a string
+    id: String,
+    /// Matching strategy for the identifier (default: legacy)
+    #[clap(long, value_enum)]
+    strategy: Option<MatchingStrategy>,
+    /// Name of a command from 'launch_commands' in the static config to run if nothing matches
+    #[clap(long)]
+    launch: Option<String>,
+    /// Monitor index (zero-indexed) to send the launched window to (requires --workspace)
+    #[clap(long, requires = "workspace")]
+    monitor: Option<usize>,
+    /// Workspace index (zero-indexed) to send the launched window to (requires --monitor)
+    #[clap(long, requires = "monitor")]
+    workspace: Option<usize>,
+}
+
 #[derive(Parser, AhkFunction)]
 struct FocusMonitorWorkspace {
     /// Target monitor index (zero-indexed)
@@ -576,6 +837,7 @@ macro_rules! gen_application_target_subcommand_args {
 
 gen_application_target_subcommand_args! {
     FloatRule,
+    StickyRule,
     ManageRule,
     UnmanageRule,
     IdentifyTrayApplication,
@@ -643,6 +905,19 @@ struct FocusFollowsMouse {
     boolean_state: BooleanState,
 }
 
+#[derive(Parser, AhkFunction)]
+struct ToggleSticky {
+    /// Where the window stays visible
+    #[clap(value_enum, short, long, default_value = "monitor")]
+    scope: StickyScope,
+}
+
+#[derive(Parser, AhkFunction)]
+struct MonitorNavigationWrap {
+    #[clap(value_enum)]
+    boolean_state: BooleanState,
+}
+
 #[derive(Parser, AhkFunction)]
 struct ActiveWindowBorder {
     #[clap(value_enum)]
@@ -688,6 +963,12 @@ struct Start {
     /// Start a TCP server on the given port to allow the direct sending of SocketMessages
     #[clap(short, long)]
     tcp_port: Option<usize>,
+    /// Address to bind the TCP server to (default: 127.0.0.1)
+    #[clap(long)]
+    tcp_bind_address: Option<String>,
+    /// Start a WebSocket server on the given localhost port to allow the sending of SocketMessages and receiving of notifications
+    #[clap(long)]
+    ws_port: Option<usize>,
     /// Start whkd in a background process
     #[clap(long)]
     whkd: bool,
@@ -715,6 +996,27 @@ struct LoadResize {
     path: PathBuf,
 }
 
+#[derive(Parser, AhkFunction)]
+struct SaveSnapshot {
+    /// Name of the snapshot
+    name: String,
+    /// What the snapshot captures
+    #[clap(value_enum, short, long, default_value = "workspace")]
+    scope: SnapshotScope,
+}
+
+#[derive(Parser, AhkFunction)]
+struct LoadSnapshot {
+    /// Name of the snapshot
+    name: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct DeleteSnapshot {
+    /// Name of the snapshot
+    name: String,
+}
+
 #[derive(Parser, AhkFunction)]
 struct LoadCustomLayout {
     /// JSON or YAML file from which the custom layout definition should be loaded
@@ -725,6 +1027,9 @@ struct LoadCustomLayout {
 struct SubscribeSocket {
     /// Name of the socket to send event notifications to
     socket: String,
+    /// Send a full snapshot first, followed by JSON Patch deltas against the previous state
+    #[clap(long)]
+    deltas: bool,
 }
 
 #[derive(Parser, AhkFunction)]
@@ -737,6 +1042,9 @@ struct UnsubscribeSocket {
 struct SubscribePipe {
     /// Name of the pipe to send event notifications to (without "\\.\pipe\" prepended)
     named_pipe: String,
+    /// Send a full snapshot first, followed by JSON Patch deltas against the previous state
+    #[clap(long)]
+    deltas: bool,
 }
 
 #[derive(Parser, AhkFunction)]
@@ -745,6 +1053,18 @@ struct UnsubscribePipe {
     named_pipe: String,
 }
 
+#[derive(Parser, AhkFunction)]
+struct ResyncSubscriber {
+    /// Name of the socket or pipe subscriber which should receive a fresh snapshot
+    subscriber: String,
+}
+
+#[derive(Parser, AhkFunction)]
+struct SubscriberQueueCapacity {
+    /// Maximum number of notifications queued for each new subscriber
+    capacity: usize,
+}
+
 #[derive(Parser, AhkFunction)]
 struct AhkAppSpecificConfiguration {
     /// YAML file from which the application-specific configurations should be loaded
@@ -773,6 +1093,16 @@ struct AltFocusHack {
     boolean_state: BooleanState,
 }
 
+#[derive(Parser, AhkFunction)]
+struct GenerateBindings {
+    /// Language of the generated client bindings
+    #[clap(long, value_enum)]
+    lang: BindingsLanguage,
+    /// File to write the bindings to (default: stdout)
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
 #[derive(Parser, AhkFunction)]
 struct EnableAutostart {
     /// Path to a static configuration JSON file
@@ -818,6 +1148,41 @@ enum SubCommand {
     State,
     /// Show a JSON representation of visible windows
     VisibleWindows,
+    /// Show a JSON list of every managed window, most recently focused first
+    SearchWindows(SearchWindows),
+    /// Focus a managed window on any monitor or workspace
+    #[clap(arg_required_else_help = true)]
+    FocusWindowByHwnd(FocusWindowByHwnd),
+    /// Show a JSON list of managed windows, most recently focused first
+    FocusHistory,
+    /// Put a mark on the focused window
+    #[clap(arg_required_else_help = true)]
+    Mark(Mark),
+    /// Focus the window with the specified mark
+    #[clap(arg_required_else_help = true)]
+    FocusMark(FocusMark),
+    /// Swap the focused window with the window with the specified mark
+    #[clap(arg_required_else_help = true)]
+    SwapWithMark(SwapWithMark),
+    /// Show a JSON map of marks to the windows they are on
+    Marks,
+    /// Put a tag on the focused window, in addition to the name of the workspace it is on
+    #[clap(arg_required_else_help = true)]
+    Tag(Tag),
+    /// Remove a tag from the focused window
+    #[clap(arg_required_else_help = true)]
+    Untag(Untag),
+    /// Show the windows carrying any of the specified tags together on a workspace of their own
+    ViewTags(ViewTags),
+    /// Hide the focused window in the specified scratchpad
+    #[clap(arg_required_else_help = true)]
+    SendToScratchpad(SendToScratchpad),
+    /// Show the window in the specified scratchpad floating on the focused workspace, or hide it again
+    #[clap(arg_required_else_help = true)]
+    ScratchpadToggle(ScratchpadToggle),
+    /// Bind the specified scratchpad to an application
+    #[clap(arg_required_else_help = true)]
+    ScratchpadRule(ScratchpadRule),
     /// Query the current window manager state
     #[clap(arg_required_else_help = true)]
     Query(Query),
@@ -835,6 +1200,17 @@ enum SubCommand {
     #[clap(arg_required_else_help = true)]
     #[clap(alias = "unsubscribe")]
     UnsubscribePipe(UnsubscribePipe),
+    /// Send a fresh state snapshot to a delta subscriber on its next notification
+    #[clap(arg_required_else_help = true)]
+    ResyncSubscriber(ResyncSubscriber),
+    /// Set the behaviour when a subscriber's notification queue is full
+    #[clap(arg_required_else_help = true)]
+    SubscriberOverflowPolicy(SubscriberOverflowPolicy),
+    /// Set the notification queue capacity for new subscribers
+    #[clap(arg_required_else_help = true)]
+    SubscriberQueueCapacity(SubscriberQueueCapacity),
+    /// Show delivery and dropped message metrics for all subscribers
+    SubscriptionMetrics,
     /// Tail komorebi.exe's process logs (cancel with Ctrl-C)
     Log,
     /// Quicksave the current resize layout dimensions
@@ -851,6 +1227,17 @@ enum SubCommand {
     #[clap(arg_required_else_help = true)]
     #[clap(alias = "load")]
     LoadResize(LoadResize),
+    /// Save the arrangement of the focused workspace or of all monitors as a named snapshot
+    #[clap(arg_required_else_help = true)]
+    SaveSnapshot(SaveSnapshot),
+    /// Rearrange the open windows which match a named snapshot into its arrangement
+    #[clap(arg_required_else_help = true)]
+    LoadSnapshot(LoadSnapshot),
+    /// Delete a named snapshot
+    #[clap(arg_required_else_help = true)]
+    DeleteSnapshot(DeleteSnapshot),
+    /// Show a JSON list of the saved snapshots
+    Snapshots,
     /// Change focus to the window in the specified direction
     #[clap(arg_required_else_help = true)]
     Focus(Focus),
@@ -861,11 +1248,22 @@ enum SubCommand {
     Minimize,
     /// Close the focused window
     Close,
+    /// Focus a managed window matching the identifier, or launch the application if there is none
+    #[clap(arg_required_else_help = true)]
+    RunOrRaise(RunOrRaise),
+    /// Run a window command on the windows matched by a selector instead of the focused window
+    #[clap(arg_required_else_help = true)]
+    Select(Select),
     /// Forcibly focus the window at the cursor with a left mouse click
     ForceFocus,
     /// Change focus to the window in the specified cycle direction
     #[clap(arg_required_else_help = true)]
     CycleFocus(CycleFocus),
+    /// Focus the window which was focused before the current one
+    FocusPreviousWindow,
+    /// Walk through previously focused windows in the specified cycle direction
+    #[clap(arg_required_else_help = true)]
+    CycleFocusHistory(CycleFocusHistory),
     /// Move the focused window in the specified cycle direction
     #[clap(arg_required_else_help = true)]
     CycleMove(CycleMove),
@@ -920,8 +1318,12 @@ enum SubCommand {
     /// Focus the specified monitor
     #[clap(arg_required_else_help = true)]
     FocusMonitor(FocusMonitor),
+    /// Split the focused display into its configured virtual monitors, or join them back together
+    ToggleVirtualMonitors,
     /// Focus the last focused workspace on the focused monitor
     FocusLastWorkspace,
+    /// Return to the most recently left workspace on any monitor
+    PopWorkspaceHistory,
     /// Focus the specified workspace on the focused monitor
     #[clap(arg_required_else_help = true)]
     FocusWorkspace(FocusWorkspace),
@@ -934,6 +1336,9 @@ enum SubCommand {
     /// Focus the specified workspace
     #[clap(arg_required_else_help = true)]
     FocusNamedWorkspace(FocusNamedWorkspace),
+    /// Focus the workspaces of the specified workspace group on all of its monitors at once
+    #[clap(arg_required_else_help = true)]
+    FocusWorkspaceGroup(FocusWorkspaceGroup),
     /// Focus the monitor in the given cycle direction
     #[clap(arg_required_else_help = true)]
     CycleMonitor(CycleMonitor),
@@ -990,6 +1395,13 @@ enum SubCommand {
     PromoteFocus,
     /// Force the retiling of all managed windows
     Retile,
+    /// Run a macro defined in the static configuration file
+    #[clap(arg_required_else_help = true)]
+    Run(Run),
+    /// Undo the last window, layout, resize or padding operation
+    Undo,
+    /// Redo the last undone operation
+    Redo,
     /// Set the monitor index preference for a monitor identified using its size
     #[clap(arg_required_else_help = true)]
     MonitorIndexPreference(MonitorIndexPreference),
@@ -1002,6 +1414,9 @@ enum SubCommand {
     /// Create these many named workspaces for the specified monitor
     #[clap(arg_required_else_help = true)]
     EnsureNamedWorkspaces(EnsureNamedWorkspaces),
+    /// Enable or disable removing empty workspaces once they are left on the specified monitor
+    #[clap(arg_required_else_help = true)]
+    DynamicWorkspaces(DynamicWorkspaces),
     /// Set the container padding for the specified workspace
     #[clap(arg_required_else_help = true)]
     ContainerPadding(ContainerPadding),
@@ -1061,6 +1476,8 @@ enum SubCommand {
     ToggleTiling,
     /// Toggle floating mode for the focused window
     ToggleFloat,
+    /// Toggle whether the focused window stays visible when switching workspaces
+    ToggleSticky(ToggleSticky),
     /// Toggle monocle mode for the focused container
     ToggleMonocle,
     /// Toggle native maximization for the focused window
@@ -1090,12 +1507,18 @@ enum SubCommand {
     CrossMonitorMoveBehaviour(CrossMonitorMoveBehaviour),
     /// Toggle the behaviour when moving windows across monitor boundaries
     ToggleCrossMonitorMoveBehaviour,
+    /// Enable or disable wrapping around to the monitor on the opposite side when there is no monitor in a direction
+    #[clap(arg_required_else_help = true)]
+    MonitorNavigationWrap(MonitorNavigationWrap),
     /// Set the operation behaviour when the focused window is not managed
     #[clap(arg_required_else_help = true)]
     UnmanagedWindowOperationBehaviour(UnmanagedWindowOperationBehaviour),
     /// Add a rule to always float the specified application
     #[clap(arg_required_else_help = true)]
     FloatRule(FloatRule),
+    /// Add a rule to keep the specified application visible when switching workspaces
+    #[clap(arg_required_else_help = true)]
+    StickyRule(StickyRule),
     /// Add a rule to always unmanage the specified application
     #[clap(arg_required_else_help = true)]
     UnmanageRule(UnmanageRule),
@@ -1181,6 +1604,9 @@ enum SubCommand {
     SocketSchema,
     /// Generate a JSON Schema of the static configuration file
     StaticConfigSchema,
+    /// Generate client bindings with message constructors, state types and a client for the WebSocket server started with 'komorebi --ws-port'
+    #[clap(arg_required_else_help = true)]
+    GenerateBindings(GenerateBindings),
     /// Generates a static configuration JSON file based on the current window manager state
     GenerateStaticConfig,
     /// Generates the komorebi.lnk shortcut in shell:startup to autostart komorebi
@@ -1486,6 +1912,31 @@ fn main() -> Result<()> {
         SubCommand::Close => {
             send_message(&SocketMessage::Close.as_bytes()?)?;
         }
+        SubCommand::RunOrRaise(arg) => {
+            let workspace = match (arg.monitor, arg.workspace) {
+                (Some(monitor), Some(workspace)) => Option::from((monitor, workspace)),
+                _ => None,
+            };
+
+            send_message(
+                &SocketMessage::RunOrRaise(
+                    IdWithIdentifier {
+                        kind: arg.identifier,
+                        id: arg.id,
+                        matching_strategy: arg.strategy,
+                    },
+                    arg.launch,
+                    workspace,
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::Select(arg) => {
+            send_message(
+                &SocketMessage::WithSelector(arg.selector()?, Box::new(arg.message()?))
+                    .as_bytes()?,
+            )?;
+        }
         SubCommand::Minimize => {
             send_message(&SocketMessage::Minimize.as_bytes()?)?;
         }
@@ -1501,12 +1952,27 @@ fn main() -> Result<()> {
         SubCommand::Retile => {
             send_message(&SocketMessage::Retile.as_bytes()?)?;
         }
+        SubCommand::Run(arg) => {
+            send_message(&SocketMessage::RunMacro(arg.name, arg.parameters).as_bytes()?)?;
+        }
+        SubCommand::Undo => {
+            send_message(&SocketMessage::Undo.as_bytes()?)?;
+        }
+        SubCommand::Redo => {
+            send_message(&SocketMessage::Redo.as_bytes()?)?;
+        }
         SubCommand::Move(arg) => {
             send_message(&SocketMessage::MoveWindow(arg.operation_direction).as_bytes()?)?;
         }
         SubCommand::CycleFocus(arg) => {
             send_message(&SocketMessage::CycleFocusWindow(arg.cycle_direction).as_bytes()?)?;
         }
+        SubCommand::FocusPreviousWindow => {
+            send_message(&SocketMessage::FocusPreviousWindow.as_bytes()?)?;
+        }
+        SubCommand::CycleFocusHistory(arg) => {
+            send_message(&SocketMessage::CycleFocusHistory(arg.cycle_direction).as_bytes()?)?;
+        }
         SubCommand::CycleMove(arg) => {
             send_message(&SocketMessage::CycleMoveWindow(arg.cycle_direction).as_bytes()?)?;
         }
@@ -1647,6 +2113,9 @@ fn main() -> Result<()> {
         SubCommand::ToggleFloat => {
             send_message(&SocketMessage::ToggleFloat.as_bytes()?)?;
         }
+        SubCommand::ToggleSticky(arg) => {
+            send_message(&SocketMessage::ToggleSticky(arg.scope).as_bytes()?)?;
+        }
         SubCommand::ToggleMonocle => {
             send_message(&SocketMessage::ToggleMonocle.as_bytes()?)?;
         }
@@ -1809,6 +2278,14 @@ fn main() -> Result<()> {
                 flags.push(format!("'--tcp-port={port}'"));
             }
 
+            if let Some(address) = arg.tcp_bind_address {
+                flags.push(format!("'--tcp-bind-address={address}'"));
+            }
+
+            if let Some(port) = arg.ws_port {
+                flags.push(format!("'--ws-port={port}'"));
+            }
+
             let script = if flags.is_empty() {
                 format!(
                     "Start-Process '{}' -WindowStyle hidden",
@@ -1906,6 +2383,9 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::FloatRule(arg) => {
             send_message(&SocketMessage::FloatRule(arg.identifier, arg.id).as_bytes()?)?;
         }
+        SubCommand::StickyRule(arg) => {
+            send_message(&SocketMessage::StickyRule(arg.identifier, arg.id).as_bytes()?)?;
+        }
         SubCommand::UnmanageRule(arg) => {
             send_message(&SocketMessage::UnmanageRule(arg.identifier, arg.id).as_bytes()?)?;
         }
@@ -1967,9 +2447,15 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::FocusMonitor(arg) => {
             send_message(&SocketMessage::FocusMonitorNumber(arg.target).as_bytes()?)?;
         }
+        SubCommand::ToggleVirtualMonitors => {
+            send_message(&SocketMessage::ToggleVirtualMonitors.as_bytes()?)?;
+        }
         SubCommand::FocusLastWorkspace => {
             send_message(&SocketMessage::FocusLastWorkspace.as_bytes()?)?;
         }
+        SubCommand::PopWorkspaceHistory => {
+            send_message(&SocketMessage::PopWorkspaceHistory.as_bytes()?)?;
+        }
         SubCommand::FocusWorkspace(arg) => {
             send_message(&SocketMessage::FocusWorkspaceNumber(arg.target).as_bytes()?)?;
         }
@@ -1988,6 +2474,9 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::FocusNamedWorkspace(arg) => {
             send_message(&SocketMessage::FocusNamedWorkspace(arg.workspace).as_bytes()?)?;
         }
+        SubCommand::FocusWorkspaceGroup(arg) => {
+            send_message(&SocketMessage::FocusWorkspaceGroup(arg.group).as_bytes()?)?;
+        }
         SubCommand::CycleMonitor(arg) => {
             send_message(&SocketMessage::CycleFocusMonitor(arg.cycle_direction).as_bytes()?)?;
         }
@@ -2027,6 +2516,12 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
                     .as_bytes()?,
             )?;
         }
+        SubCommand::DynamicWorkspaces(arg) => {
+            send_message(
+                &SocketMessage::DynamicWorkspaces(arg.monitor, arg.boolean_state.into())
+                    .as_bytes()?,
+            )?;
+        }
         SubCommand::EnsureNamedWorkspaces(arg) => {
             send_message(
                 &SocketMessage::EnsureNamedWorkspaces(arg.monitor, arg.names).as_bytes()?,
@@ -2038,6 +2533,47 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::VisibleWindows => {
             print_query(&SocketMessage::VisibleWindows.as_bytes()?);
         }
+        SubCommand::SearchWindows(arg) => {
+            print_query(&SocketMessage::SearchWindows(arg.query, arg.mode).as_bytes()?);
+        }
+        SubCommand::FocusWindowByHwnd(arg) => {
+            send_message(&SocketMessage::FocusWindowByHwnd(arg.hwnd).as_bytes()?)?;
+        }
+        SubCommand::FocusHistory => {
+            print_query(&SocketMessage::FocusHistory.as_bytes()?);
+        }
+        SubCommand::Mark(arg) => {
+            send_message(&SocketMessage::MarkWindow(arg.mark).as_bytes()?)?;
+        }
+        SubCommand::FocusMark(arg) => {
+            send_message(&SocketMessage::FocusMark(arg.mark).as_bytes()?)?;
+        }
+        SubCommand::SwapWithMark(arg) => {
+            send_message(&SocketMessage::SwapWithMark(arg.mark).as_bytes()?)?;
+        }
+        SubCommand::Marks => {
+            print_query(&SocketMessage::Marks.as_bytes()?);
+        }
+        SubCommand::Tag(arg) => {
+            send_message(&SocketMessage::TagWindow(arg.tag).as_bytes()?)?;
+        }
+        SubCommand::Untag(arg) => {
+            send_message(&SocketMessage::UntagWindow(arg.tag).as_bytes()?)?;
+        }
+        SubCommand::ViewTags(arg) => {
+            send_message(&SocketMessage::ViewTags(arg.tags).as_bytes()?)?;
+        }
+        SubCommand::SendToScratchpad(arg) => {
+            send_message(&SocketMessage::SendToScratchpad(arg.name).as_bytes()?)?;
+        }
+        SubCommand::ScratchpadToggle(arg) => {
+            send_message(&SocketMessage::ToggleScratchpad(arg.name).as_bytes()?)?;
+        }
+        SubCommand::ScratchpadRule(arg) => {
+            send_message(
+                &SocketMessage::ScratchpadRule(arg.name, arg.identifier, arg.id).as_bytes()?,
+            )?;
+        }
         SubCommand::Query(arg) => {
             print_query(&SocketMessage::Query(arg.state_query).as_bytes()?);
         }
@@ -2130,14 +2666,60 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::LoadResize(arg) => {
             send_message(&SocketMessage::Load(resolve_home_path(arg.path)?).as_bytes()?)?;
         }
+        SubCommand::SaveSnapshot(arg) => {
+            send_message(&SocketMessage::SaveSnapshot(arg.name, arg.scope).as_bytes()?)?;
+        }
+        SubCommand::LoadSnapshot(arg) => {
+            send_message(&SocketMessage::LoadSnapshot(arg.name).as_bytes()?)?;
+        }
+        SubCommand::DeleteSnapshot(arg) => {
+            send_message(&SocketMessage::DeleteSnapshot(arg.name).as_bytes()?)?;
+        }
+        SubCommand::Snapshots => {
+            print_query(&SocketMessage::Snapshots.as_bytes()?);
+        }
         SubCommand::SubscribeSocket(arg) => {
-            send_message(&SocketMessage::AddSubscriberSocket(arg.socket).as_bytes()?)?;
+            if arg.deltas {
+                send_message(
+                    &SocketMessage::AddSubscriberSocketWithMode(
+                        arg.socket,
+                        SubscriptionMode::Delta,
+                    )
+                    .as_bytes()?,
+                )?;
+            } else {
+                send_message(&SocketMessage::AddSubscriberSocket(arg.socket).as_bytes()?)?;
+            }
         }
         SubCommand::UnsubscribeSocket(arg) => {
             send_message(&SocketMessage::RemoveSubscriberSocket(arg.socket).as_bytes()?)?;
         }
         SubCommand::SubscribePipe(arg) => {
-            send_message(&SocketMessage::AddSubscriberPipe(arg.named_pipe).as_bytes()?)?;
+            if arg.deltas {
+                send_message(
+                    &SocketMessage::AddSubscriberPipeWithMode(
+                        arg.named_pipe,
+                        SubscriptionMode::Delta,
+                    )
+                    .as_bytes()?,
+                )?;
+            } else {
+                send_message(&SocketMessage::AddSubscriberPipe(arg.named_pipe).as_bytes()?)?;
+            }
+        }
+        SubCommand::ResyncSubscriber(arg) => {
+            send_message(&SocketMessage::ResyncSubscriber(arg.subscriber).as_bytes()?)?;
+        }
+        SubCommand::SubscriberOverflowPolicy(arg) => {
+            send_message(
+                &SocketMessage::SubscriberOverflowPolicy(arg.overflow_policy).as_bytes()?,
+            )?;
+        }
+        SubCommand::SubscriberQueueCapacity(arg) => {
+            send_message(&SocketMessage::SubscriberQueueCapacity(arg.capacity).as_bytes()?)?;
+        }
+        SubCommand::SubscriptionMetrics => {
+            print_query(&SocketMessage::SubscriptionMetrics.as_bytes()?);
         }
         SubCommand::UnsubscribePipe(arg) => {
             send_message(&SocketMessage::RemoveSubscriberPipe(arg.named_pipe).as_bytes()?)?;
@@ -2180,6 +2762,11 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::ToggleCrossMonitorMoveBehaviour => {
             send_message(&SocketMessage::ToggleCrossMonitorMoveBehaviour.as_bytes()?)?;
         }
+        SubCommand::MonitorNavigationWrap(arg) => {
+            send_message(
+                &SocketMessage::MonitorNavigationWrap(arg.boolean_state.into()).as_bytes()?,
+            )?;
+        }
         SubCommand::UnmanagedWindowOperationBehaviour(arg) => {
             send_message(
                 &SocketMessage::UnmanagedWindowOperationBehaviour(arg.operation_behaviour)
@@ -2287,6 +2874,21 @@ Stop-Process -Name:whkd -ErrorAction SilentlyContinue
         SubCommand::StaticConfigSchema => {
             print_query(&SocketMessage::StaticConfigSchema.as_bytes()?);
         }
+        SubCommand::GenerateBindings(arg) => {
+            let bindings = Bindings::from_schemas(
+                &send_query(&SocketMessage::SocketSchema.as_bytes()?)?,
+                &send_query(&SocketMessage::NotificationSchema.as_bytes()?)?,
+            )?;
+
+            let output = bindings.generate(arg.lang)?;
+
+            match arg.output {
+                None => print!("{output}"),
+                Some(path) => {
+                    std::fs::write(resolve_home_path(path)?, output)?;
+                }
+            }
+        }
         SubCommand::GenerateStaticConfig => {
             print_query(&SocketMessage::GenerateStaticConfig.as_bytes()?);
         }